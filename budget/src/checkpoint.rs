@@ -0,0 +1,94 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 途中経過を保存するファイル
+/// 中身の形式は、各日の実装が決める。
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    path: PathBuf,
+    interval: u64,
+}
+
+impl Checkpoint {
+    /// interval: 何ノードごとに保存するか
+    pub fn new(path: impl AsRef<Path>, interval: u64) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            interval: interval.max(1),
+        }
+    }
+
+    /// 環境変数 AOC_CHECKPOINT (ディレクトリ) に、指定の名前で保存する。
+    /// 未設定ならNone。
+    pub fn from_env(name: &str, interval: u64) -> Option<Self> {
+        let dir = env::var_os("AOC_CHECKPOINT")?;
+        Some(Self::new(
+            Path::new(&dir).join(format!("{}.checkpoint", name)),
+            interval,
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 保存するタイミングか
+    pub fn is_due(&self, nodes: u64) -> bool {
+        nodes.is_multiple_of(self.interval)
+    }
+
+    /// 保存済みの内容を読み込む。なければNone。
+    pub fn load(&self) -> io::Result<Option<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(text) => Ok(Some(text)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 書き込み途中で止まっても壊れないよう、一時ファイルに書いてから置き換える。
+    pub fn save(&self, text: &str) -> io::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    /// 完了したら消す。
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let path = env::temp_dir().join(format!("budget_test_{}.checkpoint", std::process::id()));
+        let checkpoint = Checkpoint::new(&path, 10);
+
+        assert_eq!(checkpoint.load().unwrap(), None);
+
+        checkpoint.save("row 10\n").unwrap();
+        assert_eq!(checkpoint.load().unwrap(), Some("row 10\n".to_string()));
+
+        checkpoint.clear().unwrap();
+        assert_eq!(checkpoint.load().unwrap(), None);
+        // 二回消してもエラーにならない
+        checkpoint.clear().unwrap();
+    }
+
+    #[test]
+    fn test_is_due() {
+        let checkpoint = Checkpoint::new("x", 10);
+        assert!(!checkpoint.is_due(1));
+        assert!(checkpoint.is_due(10));
+        assert!(checkpoint.is_due(20));
+    }
+}
@@ -0,0 +1,254 @@
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub use crate::checkpoint::Checkpoint;
+
+mod checkpoint;
+
+/// 時間のかかる探索を打ち切るための予算
+/// クローンしたものは中断フラグを共有する。
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    deadline: Option<Instant>,
+    max_nodes: Option<u64>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Budget {
+    /// 制限なし
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// 指定時間で打ち切る
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            deadline: Some(Instant::now() + timeout),
+            ..Self::default()
+        }
+    }
+
+    /// 指定ノード数を処理したら打ち切る
+    /// 時刻によらないので、途中で止めて再開するテストに使う。
+    pub fn with_max_nodes(max_nodes: u64) -> Self {
+        Self {
+            max_nodes: Some(max_nodes),
+            ..Self::default()
+        }
+    }
+
+    /// 環境変数 AOC_TIMEOUT (秒) から予算を作る。
+    /// 未設定なら制限なし。秒数として読めなければ警告を出して制限なし。
+    pub fn from_env() -> Self {
+        let Ok(value) = env::var("AOC_TIMEOUT") else {
+            return Self::unlimited();
+        };
+        match parse_timeout(&value) {
+            Some(timeout) => Self::with_timeout(timeout),
+            None => {
+                eprintln!("warning: ignoring invalid AOC_TIMEOUT {:?}", value);
+                Self::unlimited()
+            }
+        }
+    }
+
+    /// 外部から中断する
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_expired(&self) -> bool {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return true;
+        }
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+}
+
+/// 秒数を期間にする。負の数や NaN、大きすぎる値は None。
+fn parse_timeout(value: &str) -> Option<Duration> {
+    let secs: f64 = value.trim().parse().ok()?;
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+/// 探索の統計
+#[derive(Debug, Clone)]
+pub struct Stats {
+    /// 探索したノード(行、パターンなど)の数
+    pub nodes: u64,
+    started: Instant,
+    expired: bool,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            nodes: 0,
+            started: Instant::now(),
+            expired: false,
+        }
+    }
+}
+
+impl Stats {
+    /// 時刻の確認は重いので、この回数ごとに行う。
+    const CHECK_INTERVAL: u64 = 1024;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 途中から再開する場合に、それまでのノード数を引き継ぐ。
+    pub fn resume(nodes: u64) -> Self {
+        Self {
+            nodes,
+            ..Self::default()
+        }
+    }
+
+    /// 予算が残っていればノードを一つ数える。
+    /// 予算切れならtrueを返し、そのノードは処理しない。
+    pub fn tick(&mut self, budget: &Budget) -> bool {
+        if !self.expired {
+            self.expired = budget.max_nodes.is_some_and(|n| self.nodes >= n)
+                || (self.nodes.is_multiple_of(Self::CHECK_INTERVAL) && budget.is_expired());
+        }
+        if !self.expired {
+            self.nodes += 1;
+        }
+        self.expired
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expired
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// 予算付きで解いた結果
+#[derive(Debug)]
+pub enum Solve<T> {
+    /// 最後まで探索できた
+    Done { answer: T, stats: Stats },
+    /// 予算切れ。それまでに見つかった最善の結果を持つ。
+    TimedOut { best: Option<T>, stats: Stats },
+}
+
+impl<T> Solve<T> {
+    /// 統計の状態から結果を作る。
+    pub fn from_stats(best: Option<T>, stats: Stats) -> Self {
+        match best {
+            Some(answer) if !stats.is_expired() => Solve::Done { answer, stats },
+            best => Solve::TimedOut { best, stats },
+        }
+    }
+
+    pub fn answer(&self) -> Option<&T> {
+        match self {
+            Solve::Done { answer, .. } => Some(answer),
+            Solve::TimedOut { .. } => None,
+        }
+    }
+
+    /// 完了していれば回答、打ち切られていればそれまでの最善
+    pub fn best(&self) -> Option<&T> {
+        match self {
+            Solve::Done { answer, .. } => Some(answer),
+            Solve::TimedOut { best, .. } => best.as_ref(),
+        }
+    }
+
+    pub fn stats(&self) -> &Stats {
+        match self {
+            Solve::Done { stats, .. } | Solve::TimedOut { stats, .. } => stats,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Solve<U> {
+        match self {
+            Solve::Done { answer, stats } => Solve::Done {
+                answer: f(answer),
+                stats,
+            },
+            Solve::TimedOut { best, stats } => Solve::TimedOut {
+                best: best.map(f),
+                stats,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cancel() {
+        let budget = Budget::unlimited();
+        assert!(!budget.is_expired());
+
+        // クローンしたものから中断できること
+        budget.clone().cancel();
+        assert!(budget.is_expired());
+
+        let budget = Budget::with_timeout(Duration::ZERO);
+        assert!(budget.is_expired());
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("30"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_timeout(" 0.5\n"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_timeout("-1"), None);
+        assert_eq!(parse_timeout("NaN"), None);
+        assert_eq!(parse_timeout("inf"), None);
+        assert_eq!(parse_timeout("1e30"), None);
+        assert_eq!(parse_timeout("soon"), None);
+    }
+
+    #[test]
+    fn test_tick() {
+        let budget = Budget::unlimited();
+        let mut stats = Stats::new();
+        assert!(!stats.tick(&budget));
+
+        // 確認は一定回数ごと
+        budget.cancel();
+        assert!(!stats.tick(&budget));
+        for _ in 0..Stats::CHECK_INTERVAL {
+            stats.tick(&budget);
+        }
+        assert!(stats.is_expired());
+        assert_eq!(stats.nodes, Stats::CHECK_INTERVAL);
+
+        // ノード数の上限は毎回確認する
+        let budget = Budget::with_max_nodes(3);
+        let mut stats = Stats::resume(1);
+        assert!(!stats.tick(&budget));
+        assert!(!stats.tick(&budget));
+        assert!(stats.tick(&budget));
+        assert_eq!(stats.nodes, 3);
+    }
+
+    #[test]
+    fn test_from_stats() {
+        let solve = Solve::from_stats(Some(10), Stats::new());
+        assert_eq!(solve.answer(), Some(&10));
+
+        let mut stats = Stats::new();
+        let budget = Budget::unlimited();
+        budget.cancel();
+        stats.tick(&budget);
+
+        let solve = Solve::from_stats(Some(5), stats).map(|n| n * 2);
+        assert!(matches!(solve, Solve::TimedOut { best: Some(10), .. }));
+        assert_eq!(solve.answer(), None);
+        assert_eq!(solve.best(), Some(&10));
+        assert_eq!(solve.stats().nodes, 0);
+    }
+}
@@ -2,13 +2,20 @@ use std::fs;
 use std::io::BufReader;
 
 use anyhow::Result;
-use day01::read_sum_list;
+use day01::CalorieReport;
+use tools::input::{input_path, json_requested};
 
 fn main() -> Result<()> {
-    let r = BufReader::new(fs::File::open("./day01/data/input.txt")?);
-    let list = read_sum_list(r)?;
-    let max = *list.iter().max().expect("No max found");
+    let path = input_path("day01");
+    let r = BufReader::new(fs::File::open(path)?);
+    let report = CalorieReport::read(r)?;
+    if json_requested() {
+        println!("report: {}", report.to_json());
+    }
 
-    println!("answer: {}", max);
+    let max = report.max().expect("No max found");
+
+    println!("elf: {}", max.index);
+    println!("answer: {}", max.total());
     Ok(())
 }
@@ -2,14 +2,18 @@ use std::fs;
 use std::io::BufReader;
 
 use anyhow::Result;
-use day01::read_sum_list;
-use itertools::Itertools;
+use day01::CalorieReport;
+use tools::input::{input_path, json_requested};
 
 fn main() -> Result<()> {
-    let r = BufReader::new(fs::File::open("./day01/data/input.txt")?);
-    let list = read_sum_list(r)?;
+    let path = input_path("day01");
+    let r = BufReader::new(fs::File::open(path)?);
+    let report = CalorieReport::read(r)?;
+    if json_requested() {
+        println!("report: {}", report.to_json());
+    }
 
-    let sum_of_top_three: u32 = list.iter().sorted().rev().take(3).sum();
+    let sum_of_top_three = report.top_sum(3);
     println!("answer: {}", sum_of_top_three);
 
     Ok(())
@@ -6,6 +6,12 @@ use nom::character::complete;
 use nom::multi::separated_list1;
 use nom::IResult;
 
+mod report;
+mod stream;
+
+pub use report::{CalorieReport, Elf};
+pub use stream::read_top_k;
+
 /// 空行ごとに合計した値のVecを取得する。
 pub fn read_sum_list(mut r: impl BufRead) -> Result<Vec<u32>> {
     let mut buffer = String::new();
@@ -0,0 +1,255 @@
+use std::io::BufRead;
+
+use anyhow::Result;
+
+use crate::parse_input;
+
+/// エルフ1人分の荷物。indexは入力での並び順(0始まり)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elf {
+    pub index: usize,
+    pub items: Vec<u32>,
+}
+
+impl Elf {
+    pub fn total(&self) -> u64 {
+        self.items.iter().map(|&c| c as u64).sum()
+    }
+
+    pub fn item_count(&self) -> usize {
+        self.items.len()
+    }
+}
+
+/// エルフごとのカロリーの集計。
+#[derive(Debug, Clone)]
+pub struct CalorieReport {
+    elves: Vec<Elf>,
+}
+
+impl CalorieReport {
+    pub fn read(mut r: impl BufRead) -> Result<Self> {
+        let mut buffer = String::new();
+        r.read_to_string(&mut buffer)?;
+
+        let list = match parse_input(&buffer) {
+            Ok((_, v)) => v,
+            Err(e) => return Err(anyhow::anyhow!("Error parsing input: {}", e.to_string())),
+        };
+
+        Ok(Self::from_items(list))
+    }
+
+    pub fn from_items(list: Vec<Vec<u32>>) -> Self {
+        let elves = list
+            .into_iter()
+            .enumerate()
+            .map(|(index, items)| Elf { index, items })
+            .collect();
+
+        Self { elves }
+    }
+
+    pub fn elves(&self) -> &[Elf] {
+        &self.elves
+    }
+
+    pub fn elf(&self, index: usize) -> Option<&Elf> {
+        self.elves.get(index)
+    }
+
+    /// 合計の多い順。同じ合計なら入力順。
+    pub fn ranking(&self) -> Vec<&Elf> {
+        let mut elves: Vec<&Elf> = self.elves.iter().collect();
+        elves.sort_by(|a, b| b.total().cmp(&a.total()).then(a.index.cmp(&b.index)));
+        elves
+    }
+
+    pub fn max(&self) -> Option<&Elf> {
+        self.ranking().first().copied()
+    }
+
+    /// 上位n人。n人目と同じ合計のエルフも含めるので、n人より多くなることがある。
+    pub fn top(&self, n: usize) -> Vec<&Elf> {
+        let ranking = self.ranking();
+        let Some(border) = n.checked_sub(1).and_then(|i| ranking.get(i)) else {
+            return ranking.into_iter().take(n).collect();
+        };
+
+        let border = border.total();
+        ranking
+            .into_iter()
+            .take_while(|elf| elf.total() >= border)
+            .collect()
+    }
+
+    /// 上位n人の合計。同点でもちょうどn人分だけ足す。
+    pub fn top_sum(&self, n: usize) -> u64 {
+        self.ranking().iter().take(n).map(|elf| elf.total()).sum()
+    }
+
+    /// 順位(1始まり)。同じ合計のエルフは同じ順位になる。
+    pub fn rank(&self, index: usize) -> Option<usize> {
+        let total = self.elf(index)?.total();
+        let higher = self.elves.iter().filter(|elf| elf.total() > total).count();
+        Some(higher + 1)
+    }
+
+    /// 全員の順位を入力順で返す。rankを1人ずつ呼ぶと遅いので並べ替え1回で出す。
+    pub fn ranks(&self) -> Vec<usize> {
+        let mut ranks = vec![0; self.elves.len()];
+        let mut prev: Option<(u64, usize)> = None;
+        for (i, elf) in self.ranking().into_iter().enumerate() {
+            let rank = match prev {
+                Some((total, rank)) if total == elf.total() => rank,
+                _ => i + 1,
+            };
+            ranks[elf.index] = rank;
+            prev = Some((elf.total(), rank));
+        }
+        ranks
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.elves.is_empty() {
+            return None;
+        }
+        let sum: u64 = self.elves.iter().map(|elf| elf.total()).sum();
+        Some(sum as f64 / self.elves.len() as f64)
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        self.percentile(50.0)
+    }
+
+    /// p(0〜100)パーセンタイル。間の値は線形補間する。
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        if self.elves.is_empty() || !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+
+        let mut totals: Vec<u64> = self.elves.iter().map(|elf| elf.total()).collect();
+        totals.sort();
+
+        let pos = p / 100.0 * (totals.len() - 1) as f64;
+        let lower = pos.floor() as usize;
+        let upper = pos.ceil() as usize;
+        let weight = pos - lower as f64;
+
+        Some(totals[lower] as f64 * (1.0 - weight) + totals[upper] as f64 * weight)
+    }
+
+    /// エルフごとの荷物の数(入力順)。
+    pub fn item_counts(&self) -> Vec<usize> {
+        self.elves.iter().map(|elf| elf.item_count()).collect()
+    }
+
+    /// 集計結果をJSONにする。
+    pub fn to_json(&self) -> String {
+        let ranks = self.ranks();
+        let elves = self
+            .elves
+            .iter()
+            .map(|elf| {
+                format!(
+                    r#"{{"index":{},"items":[{}],"total":{},"rank":{}}}"#,
+                    elf.index,
+                    join(&elf.items),
+                    elf.total(),
+                    ranks[elf.index],
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let top3: Vec<usize> = self.top(3).iter().map(|elf| elf.index).collect();
+
+        format!(
+            r#"{{"elves":[{}],"top3":[{}],"mean":{},"median":{}}}"#,
+            elves,
+            join(&top3),
+            to_json_number(self.mean()),
+            to_json_number(self.median()),
+        )
+    }
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn to_json_number(value: Option<f64>) -> String {
+    value.map_or("null".to_string(), |v| v.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> CalorieReport {
+        CalorieReport::read(include_str!("../data/sample.txt").as_bytes()).unwrap()
+    }
+
+    fn indices(elves: &[&Elf]) -> Vec<usize> {
+        elves.iter().map(|elf| elf.index).collect()
+    }
+
+    #[test]
+    fn test_top() {
+        let report = sample();
+
+        assert_eq!(report.max().map(|elf| elf.total()), Some(24000));
+        assert_eq!(report.max().map(|elf| elf.index), Some(3));
+        assert_eq!(indices(&report.top(3)), vec![3, 2, 4]);
+        assert_eq!(report.top_sum(3), 45000);
+    }
+
+    #[test]
+    fn test_top_with_ties() {
+        let report = CalorieReport::from_items(vec![vec![5], vec![3], vec![2, 3], vec![1]]);
+
+        assert_eq!(indices(&report.top(1)), vec![0, 2]);
+        assert_eq!(indices(&report.top(3)), vec![0, 2, 1]);
+        assert_eq!(indices(&report.top(10)), vec![0, 2, 1, 3]);
+        assert_eq!(report.top(0), Vec::<&Elf>::new());
+        assert_eq!(report.top_sum(1), 5);
+
+        assert_eq!(report.rank(0), Some(1));
+        assert_eq!(report.rank(2), Some(1));
+        assert_eq!(report.rank(1), Some(3));
+        assert_eq!(report.rank(3), Some(4));
+        assert_eq!(report.rank(4), None);
+        assert_eq!(report.ranks(), vec![1, 3, 1, 4]);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let report = sample();
+
+        assert_eq!(report.mean(), Some(11000.0));
+        assert_eq!(report.median(), Some(10000.0));
+        assert_eq!(report.percentile(0.0), Some(4000.0));
+        assert_eq!(report.percentile(100.0), Some(24000.0));
+        assert_eq!(report.percentile(25.0), Some(6000.0));
+        assert_eq!(report.percentile(87.5), Some(17500.0));
+        assert_eq!(report.percentile(101.0), None);
+        assert_eq!(report.item_counts(), vec![3, 1, 2, 3, 1]);
+
+        let empty = CalorieReport::from_items(vec![]);
+        assert_eq!(empty.mean(), None);
+        assert_eq!(empty.median(), None);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let report = CalorieReport::from_items(vec![vec![1, 2], vec![4]]);
+        assert_eq!(
+            report.to_json(),
+            r#"{"elves":[{"index":0,"items":[1,2],"total":3,"rank":2},{"index":1,"items":[4],"total":4,"rank":1}],"top3":[1,0],"mean":3.5,"median":3.5}"#
+        );
+    }
+}
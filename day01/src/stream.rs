@@ -0,0 +1,164 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::BufRead;
+
+use anyhow::{anyhow, Result};
+
+/// 1行ずつ読みながら、合計の大きい方からk個を返す(降順)。
+/// 保持するのはk個のヒープだけなので、入力がどれだけ大きくてもメモリは増えない。
+/// CRLF、末尾の空行、連続する空行はそのまま受け付ける。
+pub fn read_top_k(mut r: impl BufRead, k: usize) -> Result<Vec<u64>> {
+    let mut heap = TopK::new(k);
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut sum: Option<u64> = None;
+
+    loop {
+        line.clear();
+        if r.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+
+        let entry = line.trim();
+        if entry.is_empty() {
+            if let Some(sum) = sum.take() {
+                heap.push(sum);
+            }
+            continue;
+        }
+
+        let calorie: u64 = entry
+            .parse()
+            .map_err(|_| anyhow!("line {}: not a number: {:?}", line_number, entry))?;
+        let total = sum
+            .unwrap_or(0)
+            .checked_add(calorie)
+            .ok_or_else(|| anyhow!("line {}: total calories overflow u64", line_number))?;
+        sum = Some(total);
+    }
+
+    if let Some(sum) = sum {
+        heap.push(sum);
+    }
+
+    Ok(heap.into_sorted_vec())
+}
+
+/// 大きい方からk個だけを残す最小ヒープ。
+struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<u64>>,
+}
+
+impl TopK {
+    fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    fn push(&mut self, value: u64) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(value));
+        } else if self.heap.peek().is_some_and(|Reverse(min)| *min < value) {
+            self.heap.pop();
+            self.heap.push(Reverse(value));
+        }
+    }
+
+    fn into_sorted_vec(self) -> Vec<u64> {
+        // Reverseの昇順 = 値の降順
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(v)| v)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, BufReader, Read};
+
+    use super::*;
+
+    #[test]
+    fn test_read_top_k() {
+        let r = include_str!("../data/sample.txt").as_bytes();
+        assert_eq!(read_top_k(r, 1).unwrap(), vec![24000]);
+
+        let r = include_str!("../data/sample.txt").as_bytes();
+        let top = read_top_k(r, 3).unwrap();
+        assert_eq!(top, vec![24000, 11000, 10000]);
+        assert_eq!(top.iter().sum::<u64>(), 45000);
+
+        let r = include_str!("../data/sample.txt").as_bytes();
+        assert_eq!(read_top_k(r, 0).unwrap(), Vec::<u64>::new());
+
+        let r = include_str!("../data/sample.txt").as_bytes();
+        assert_eq!(
+            read_top_k(r, 10).unwrap(),
+            vec![24000, 11000, 10000, 6000, 4000]
+        );
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        let input = "\r\n1\r\n2\r\n\r\n\r\n\r\n5\r\n\r\n\n\n";
+        assert_eq!(read_top_k(input.as_bytes(), 3).unwrap(), vec![5, 3]);
+
+        assert_eq!(read_top_k("".as_bytes(), 3).unwrap(), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_errors() {
+        let input = "1\n2\n\nabc\n";
+        let err = read_top_k(input.as_bytes(), 3).unwrap_err();
+        assert_eq!(err.to_string(), r#"line 4: not a number: "abc""#);
+
+        let input = format!("{}\n1\n", u64::MAX);
+        let err = read_top_k(input.as_bytes(), 3).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: total calories overflow u64");
+    }
+
+    /// "1\n2\n...\n\n" を延々と生成する入力
+    struct Generator {
+        groups: u64,
+        current: u64,
+        buffer: Vec<u8>,
+    }
+
+    impl Read for Generator {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.buffer.is_empty() {
+                if self.current == self.groups {
+                    return Ok(0);
+                }
+                self.current += 1;
+                for i in 1..=self.current % 100 {
+                    self.buffer.extend(format!("{}\n", i).bytes());
+                }
+                self.buffer.push(b'\n');
+            }
+
+            let n = buf.len().min(self.buffer.len());
+            buf[..n].copy_from_slice(&self.buffer[..n]);
+            self.buffer.drain(..n);
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_generated() {
+        let r = BufReader::new(Generator {
+            groups: 10000,
+            current: 0,
+            buffer: Vec::new(),
+        });
+
+        // 99個のグループ(1..=99の合計 4950)が100個ある
+        assert_eq!(read_top_k(r, 3).unwrap(), vec![4950, 4950, 4950]);
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::{Outcome, Shape};
+
+/// 組み込みのじゃんけん
+const ROCK_PAPER_SCISSORS: &str = include_str!("../data/rock_paper_scissors.txt");
+
+/// 手の定義
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeDef {
+    pub name: String,
+    pub score: u32,
+}
+
+/// 手とその点数、勝ち負けの関係で決まるゲーム。
+/// 手は定義した順番の番号で扱う。
+///
+/// 定義ファイルの書式
+/// ```text
+/// # 名前 点数
+/// shape Rock 1
+/// shape Paper 2
+/// shape Scissors 3
+/// # 勝つ手 負ける手
+/// beats Paper Rock
+/// beats Scissors Paper
+/// beats Rock Scissors
+/// ```
+/// `beats` の代わりに `cyclic` と書くと、奇数個の手について
+/// それぞれが直前の (N-1)/2 個の手に勝つ関係になる。
+#[derive(Debug, Clone)]
+pub struct Game {
+    shapes: Vec<ShapeDef>,
+    // (勝つ手, 負ける手)
+    beats: HashSet<(usize, usize)>,
+}
+
+impl Game {
+    pub fn new(shapes: Vec<ShapeDef>, beats: HashSet<(usize, usize)>) -> Result<Self> {
+        let mut names = HashSet::new();
+        for shape in &shapes {
+            if !names.insert(shape.name.as_str()) {
+                bail!("duplicate shape: {}", shape.name);
+            }
+        }
+
+        for a in 0..shapes.len() {
+            if beats.contains(&(a, a)) {
+                bail!("{} beats itself", shapes[a].name);
+            }
+            for b in (a + 1)..shapes.len() {
+                match (beats.contains(&(a, b)), beats.contains(&(b, a))) {
+                    (true, false) | (false, true) => {}
+                    (true, true) => {
+                        bail!("{} and {} beat each other", shapes[a].name, shapes[b].name)
+                    }
+                    (false, false) => {
+                        bail!("no rule for {} and {}", shapes[a].name, shapes[b].name)
+                    }
+                }
+            }
+        }
+
+        Ok(Self { shapes, beats })
+    }
+
+    /// 手を並べた順に、それぞれが直前の (N-1)/2 個の手に勝つゲーム。
+    /// 点数は並べた順に1から。
+    pub fn cyclic(names: &[&str]) -> Result<Self> {
+        let shapes = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| ShapeDef {
+                name: name.to_string(),
+                score: i as u32 + 1,
+            })
+            .collect::<Vec<_>>();
+        let beats = cyclic_beats(shapes.len())?;
+
+        Self::new(shapes, beats)
+    }
+
+    /// 定義ファイルから読み込む。
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut shapes = Vec::new();
+        let mut index = HashMap::new();
+        let mut rules = Vec::new();
+        let mut cyclic = false;
+
+        for (i, line) in spec.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[..] {
+                [] => {}
+                [comment, ..] if comment.starts_with('#') => {}
+                ["shape", name, score] => {
+                    let score = score
+                        .parse()
+                        .map_err(|_| anyhow!("line {}: illegal score: {}", i + 1, score))?;
+                    index.insert(name.to_string(), shapes.len());
+                    shapes.push(ShapeDef {
+                        name: name.to_string(),
+                        score,
+                    });
+                }
+                ["beats", winner, loser] => rules.push((i + 1, winner, loser)),
+                ["cyclic"] => cyclic = true,
+                _ => bail!("line {}: illegal line: {}", i + 1, line),
+            }
+        }
+
+        let mut beats = if cyclic {
+            cyclic_beats(shapes.len())?
+        } else {
+            HashSet::new()
+        };
+        for (line, winner, loser) in rules {
+            let find = |name: &str| {
+                index
+                    .get(name)
+                    .copied()
+                    .ok_or_else(|| anyhow!("line {}: unknown shape: {}", line, name))
+            };
+            beats.insert((find(winner)?, find(loser)?));
+        }
+
+        Self::new(shapes, beats)
+    }
+
+    /// 組み込みのグー、チョキ、パー。番号は `Shape::index` と同じ。
+    pub fn rock_paper_scissors() -> &'static Self {
+        static GAME: OnceLock<Game> = OnceLock::new();
+        GAME.get_or_init(|| Self::parse(ROCK_PAPER_SCISSORS).expect("illegal built-in game"))
+    }
+
+    pub fn shapes(&self) -> &[ShapeDef] {
+        &self.shapes
+    }
+
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// 名前から手の番号を探す
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.shapes.iter().position(|shape| shape.name == name)
+    }
+
+    pub fn get_score(&self, shape: usize) -> u32 {
+        self.shapes[shape].score
+    }
+
+    /// 勝ち負け判定
+    pub fn judge(&self, opponent: usize, my_shape: usize) -> Outcome {
+        if opponent == my_shape {
+            Outcome::Draw
+        } else if self.beats.contains(&(my_shape, opponent)) {
+            Outcome::Win
+        } else {
+            Outcome::Lose
+        }
+    }
+
+    /// 結果に合わせた手をすべて返す
+    pub fn choose_shapes(&self, opponent: usize, outcome: &Outcome) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&shape| self.judge(opponent, shape) == *outcome)
+            .collect()
+    }
+
+    /// 結果に合わせた手のうち、点数が一番高いもの
+    pub fn choose_shape(&self, opponent: usize, outcome: &Outcome) -> Option<usize> {
+        self.choose_shapes(opponent, outcome)
+            .into_iter()
+            .max_by_key(|&shape| (self.get_score(shape), std::cmp::Reverse(shape)))
+    }
+
+    /// 1ラウンドの点数(自分の手 + 勝敗)
+    pub fn round_score(&self, opponent: usize, my_shape: usize) -> u32 {
+        self.get_score(my_shape) + self.judge(opponent, my_shape).get_score()
+    }
+}
+
+fn cyclic_beats(n: usize) -> Result<HashSet<(usize, usize)>> {
+    if n.is_multiple_of(2) {
+        bail!("cyclic game needs an odd number of shapes: {}", n);
+    }
+
+    Ok((0..n)
+        .flat_map(|i| (1..=n / 2).map(move |d| (i, (i + n - d) % n)))
+        .collect())
+}
+
+/// 組み込みのじゃんけんで判定する
+pub fn judge(opponent: &Shape, my_shape: &Shape) -> Outcome {
+    Game::rock_paper_scissors().judge(opponent.index(), my_shape.index())
+}
+
+/// 組み込みのじゃんけんで、結果に合わせた手を返す
+pub fn get_shape(opponent: &Shape, outcome: &Outcome) -> Shape {
+    let shape = Game::rock_paper_scissors()
+        .choose_shape(opponent.index(), outcome)
+        .expect("every outcome is possible");
+    Shape::from_index(shape).expect("built-in game has 3 shapes")
+}
+
+#[cfg(test)]
+mod tests {
+    use Outcome::*;
+    use Shape::*;
+
+    use super::*;
+
+    #[test]
+    fn test_rock_paper_scissors() {
+        assert_eq!(judge(&Rock, &Paper), Win);
+        assert_eq!(judge(&Rock, &Scissors), Lose);
+        assert_eq!(judge(&Scissors, &Rock), Win);
+        assert_eq!(judge(&Paper, &Paper), Draw);
+
+        assert_eq!(get_shape(&Rock, &Draw), Rock);
+        assert_eq!(get_shape(&Paper, &Lose), Rock);
+        assert_eq!(get_shape(&Scissors, &Win), Rock);
+
+        let game = Game::rock_paper_scissors();
+        assert_eq!(game.find("Paper"), Some(Paper.index()));
+        assert_eq!(game.get_score(Scissors.index()), Scissors.get_score());
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let game = Game::parse(include_str!("../data/rpsls.txt")).unwrap();
+        let [rock, paper, scissors, lizard, spock] =
+            ["Rock", "Paper", "Scissors", "Lizard", "Spock"].map(|name| game.find(name).unwrap());
+
+        assert_eq!(game.judge(rock, lizard), Lose);
+        assert_eq!(game.judge(lizard, spock), Lose);
+        assert_eq!(game.judge(spock, scissors), Lose);
+        assert_eq!(game.judge(paper, spock), Lose);
+        assert_eq!(game.judge(rock, spock), Win);
+        assert_eq!(game.judge(lizard, lizard), Draw);
+
+        // Rockに勝つのはPaperとSpock。点数の高いSpockを選ぶ
+        assert_eq!(game.choose_shapes(rock, &Win), vec![paper, spock]);
+        assert_eq!(game.choose_shape(rock, &Win), Some(spock));
+        assert_eq!(game.round_score(rock, spock), 5 + 6);
+    }
+
+    #[test]
+    fn test_cyclic() {
+        let game = Game::cyclic(&["A", "B", "C", "D", "E"]).unwrap();
+        for a in 0..game.len() {
+            let wins = (0..game.len()).filter(|&b| game.judge(b, a) == Win).count();
+            assert_eq!(wins, 2);
+        }
+
+        let game = Game::parse("shape R 1\nshape P 2\nshape S 3\ncyclic\n").unwrap();
+        for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+            assert_eq!(game.judge(a, b), Win);
+            assert_eq!(Game::rock_paper_scissors().judge(a, b), Win);
+        }
+
+        assert!(Game::cyclic(&["A", "B"]).is_err());
+    }
+
+    #[test]
+    fn test_illegal_spec() {
+        let err = Game::parse("shape A 1\nshape B 2\n").unwrap_err();
+        assert_eq!(err.to_string(), "no rule for A and B");
+
+        let err = Game::parse("shape A 1\nshape B 2\nbeats A B\nbeats B A\n").unwrap_err();
+        assert_eq!(err.to_string(), "A and B beat each other");
+
+        let err = Game::parse("shape A 1\nbeats A C\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: unknown shape: C");
+
+        let err = Game::parse("shape A x\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: illegal score: x");
+
+        let err = Game::parse("shape A 1\nshape A 2\n").unwrap_err();
+        assert_eq!(err.to_string(), "duplicate shape: A");
+    }
+}
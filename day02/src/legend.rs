@@ -0,0 +1,432 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::io::BufRead;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::{get_shape, judge, Outcome, Shape};
+
+/// 2列目の記号の意味
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    /// 自分の手 (part1)
+    Shape(BTreeMap<char, Shape>),
+    /// 勝敗 (part2)
+    Outcome(BTreeMap<char, Outcome>),
+}
+
+/// 攻略本の記号の意味
+#[derive(Debug, Clone, PartialEq)]
+pub struct Legend {
+    pub opponent: BTreeMap<char, Shape>,
+    pub response: Response,
+}
+
+impl Legend {
+    pub fn new(opponent: BTreeMap<char, Shape>, response: Response) -> Self {
+        Self { opponent, response }
+    }
+
+    /// part1: A/B/C と X/Y/Z はどちらも Rock/Paper/Scissors
+    pub fn part1() -> Self {
+        Self::new(
+            abc(),
+            Response::Shape(symbols("XYZ", [Shape::Rock, Shape::Paper, Shape::Scissors])),
+        )
+    }
+
+    /// part2: X/Y/Z は Lose/Draw/Win
+    pub fn part2() -> Self {
+        Self::new(
+            abc(),
+            Response::Outcome(symbols("XYZ", [Outcome::Lose, Outcome::Draw, Outcome::Win])),
+        )
+    }
+
+    /// 1行に `列 記号 意味` を並べた凡例を読む。
+    /// 列は `opponent` (相手の手、意味は Rock/Paper/Scissors) か `response`。
+    /// response の意味は Rock/Paper/Scissors (自分の手) か Win/Draw/Lose (勝敗) で、混ぜられない。
+    ///
+    /// ```text
+    /// opponent A Rock
+    /// opponent B Paper
+    /// opponent C Scissors
+    /// response X Lose
+    /// response Y Draw
+    /// response Z Win
+    /// ```
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut opponent = BTreeMap::new();
+        let mut shapes = BTreeMap::new();
+        let mut outcomes = BTreeMap::new();
+        let mut responses = HashSet::new();
+
+        for (i, line) in spec.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (column, symbol, meaning) = match words[..] {
+                [] => continue,
+                [column, symbol, meaning] if symbol.chars().count() == 1 => {
+                    (column, symbol.chars().next().unwrap(), meaning)
+                }
+                _ => bail!("line {}: illegal line: {}", i + 1, line),
+            };
+
+            let inserted = match column {
+                "opponent" => {
+                    let shape = parse_shape_name(meaning).ok_or_else(|| {
+                        anyhow!("line {}: opponent must be a shape: {}", i + 1, meaning)
+                    })?;
+                    opponent.insert(symbol, shape).is_none()
+                }
+                "response" => {
+                    if let Some(shape) = parse_shape_name(meaning) {
+                        shapes.insert(symbol, shape);
+                    } else if let Some(outcome) = parse_outcome_name(meaning) {
+                        outcomes.insert(symbol, outcome);
+                    } else {
+                        bail!("line {}: unknown meaning: {}", i + 1, meaning);
+                    }
+                    responses.insert(symbol)
+                }
+                _ => bail!("line {}: unknown column: {}", i + 1, column),
+            };
+            if !inserted {
+                bail!("line {}: duplicate symbol: {}", i + 1, symbol);
+            }
+        }
+
+        if opponent.is_empty() {
+            bail!("no symbols for the first column");
+        }
+        let response = match (shapes.is_empty(), outcomes.is_empty()) {
+            (false, true) => Response::Shape(shapes),
+            (true, false) => Response::Outcome(outcomes),
+            (true, true) => bail!("no symbols for the second column"),
+            (false, false) => bail!("second column mixes shapes and outcomes"),
+        };
+
+        Ok(Self::new(opponent, response))
+    }
+
+    /// 1ラウンドの点数
+    pub fn score(&self, opponent: char, response: char) -> Result<u32> {
+        let opponent = self
+            .opponent
+            .get(&opponent)
+            .ok_or_else(|| anyhow!("unknown symbol: {}", opponent))?;
+        let unknown = || anyhow!("unknown symbol: {}", response);
+
+        let (my_shape, outcome) = match &self.response {
+            Response::Shape(shapes) => {
+                let my_shape = shapes.get(&response).ok_or_else(unknown)?;
+                (my_shape.clone(), judge(opponent, my_shape))
+            }
+            Response::Outcome(outcomes) => {
+                let outcome = outcomes.get(&response).ok_or_else(unknown)?;
+                (get_shape(opponent, outcome), outcome.clone())
+            }
+        };
+
+        Ok(my_shape.get_score() + outcome.get_score())
+    }
+}
+
+impl fmt::Display for Legend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut words: Vec<String> = self
+            .opponent
+            .iter()
+            .map(|(symbol, shape)| format!("{}={:?}", symbol, shape))
+            .collect();
+        match &self.response {
+            Response::Shape(shapes) => words.extend(
+                shapes
+                    .iter()
+                    .map(|(symbol, shape)| format!("{}={:?}", symbol, shape)),
+            ),
+            Response::Outcome(outcomes) => words.extend(
+                outcomes
+                    .iter()
+                    .map(|(symbol, outcome)| format!("{}={:?}", symbol, outcome)),
+            ),
+        }
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+/// 記号のまま読み込んだ攻略本。凡例を変えて何度でも採点できる。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Guide {
+    pub rounds: Vec<(char, char)>,
+}
+
+impl Guide {
+    pub fn read(r: impl BufRead) -> Result<Self> {
+        let mut rounds = Vec::new();
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let round = match words[..] {
+                [] => continue,
+                [a, b] if a.chars().count() == 1 && b.chars().count() == 1 => {
+                    (a.chars().next().unwrap(), b.chars().next().unwrap())
+                }
+                _ => bail!("line {}: illegal round: {}", i + 1, line),
+            };
+            rounds.push(round);
+        }
+
+        Ok(Self { rounds })
+    }
+
+    /// 凡例に従った合計点
+    pub fn score(&self, legend: &Legend) -> Result<u32> {
+        self.rounds
+            .iter()
+            .map(|(opponent, response)| legend.score(*opponent, *response))
+            .sum()
+    }
+
+    /// 凡例に従った相手の手の並び
+    pub fn opponent_shapes(&self, legend: &Legend) -> Result<Vec<Shape>> {
+        self.rounds
+            .iter()
+            .map(|(opponent, _)| {
+                legend
+                    .opponent
+                    .get(opponent)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("unknown symbol: {}", opponent))
+            })
+            .collect()
+    }
+
+    /// 候補の凡例それぞれでの合計点
+    pub fn score_all(&self, legends: &[Legend]) -> Result<Vec<u32>> {
+        legends.iter().map(|legend| self.score(legend)).collect()
+    }
+
+    /// 攻略本の2列目に出てくる記号を、それぞれ違う手に割り当てるすべての凡例と合計点。
+    /// 相手の手は opponent に従う。記号が X/Y/Z の3つなら 3! 通り。
+    pub fn search_mappings(&self, opponent: &BTreeMap<char, Shape>) -> Result<Vec<(Legend, u32)>> {
+        use Shape::{Paper, Rock, Scissors};
+
+        let permutations = [
+            [Rock, Paper, Scissors],
+            [Rock, Scissors, Paper],
+            [Paper, Rock, Scissors],
+            [Paper, Scissors, Rock],
+            [Scissors, Rock, Paper],
+            [Scissors, Paper, Rock],
+        ];
+
+        let symbols: BTreeSet<char> = self.rounds.iter().map(|(_, response)| *response).collect();
+        if symbols.len() > 3 {
+            bail!(
+                "{} symbols in the second column, but only 3 shapes",
+                symbols.len()
+            );
+        }
+
+        // 記号が3つより少なければ、先頭だけが同じ並びは1つにまとめる
+        let mut assignments: Vec<&[Shape]> = Vec::new();
+        for shapes in &permutations {
+            let shapes = &shapes[..symbols.len()];
+            if !assignments.contains(&shapes) {
+                assignments.push(shapes);
+            }
+        }
+
+        assignments
+            .into_iter()
+            .map(|shapes| {
+                let response = symbols
+                    .iter()
+                    .copied()
+                    .zip(shapes.iter().cloned())
+                    .collect();
+                let legend = Legend::new(opponent.clone(), Response::Shape(response));
+                let score = self.score(&legend)?;
+                Ok((legend, score))
+            })
+            .collect()
+    }
+
+    /// 合計点が最大になる割り当て
+    pub fn best_mapping(&self, opponent: &BTreeMap<char, Shape>) -> Result<(Legend, u32)> {
+        let mappings = self.search_mappings(opponent)?;
+        // 同点なら先の割り当てを選ぶ
+        Ok(mappings
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, score)| *score)
+            .expect("at least one mapping"))
+    }
+
+    /// 合計点が最小になる割り当て
+    pub fn worst_mapping(&self, opponent: &BTreeMap<char, Shape>) -> Result<(Legend, u32)> {
+        let mappings = self.search_mappings(opponent)?;
+        Ok(mappings
+            .into_iter()
+            .min_by_key(|(_, score)| *score)
+            .expect("at least one mapping"))
+    }
+}
+
+fn abc() -> BTreeMap<char, Shape> {
+    symbols("ABC", [Shape::Rock, Shape::Paper, Shape::Scissors])
+}
+
+fn symbols<T>(symbols: &str, meanings: [T; 3]) -> BTreeMap<char, T> {
+    symbols.chars().zip(meanings).collect()
+}
+
+fn parse_shape_name(name: &str) -> Option<Shape> {
+    match name {
+        "Rock" => Some(Shape::Rock),
+        "Paper" => Some(Shape::Paper),
+        "Scissors" => Some(Shape::Scissors),
+        _ => None,
+    }
+}
+
+fn parse_outcome_name(name: &str) -> Option<Outcome> {
+    match name {
+        "Win" => Some(Outcome::Win),
+        "Draw" => Some(Outcome::Draw),
+        "Lose" => Some(Outcome::Lose),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn sample() -> Guide {
+        Guide::read(include_str!("../data/sample.txt").as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_score() {
+        let guide = sample();
+        assert_eq!(guide.rounds, vec![('A', 'Y'), ('B', 'X'), ('C', 'Z')]);
+
+        assert_eq!(guide.score(&Legend::part1()).unwrap(), 15);
+        assert_eq!(guide.score(&Legend::part2()).unwrap(), 12);
+        assert_eq!(
+            guide
+                .score_all(&[Legend::part1(), Legend::part2()])
+                .unwrap(),
+            vec![15, 12]
+        );
+    }
+
+    #[test]
+    fn test_parse_legend() {
+        let legend = Legend::parse(indoc! {"
+            opponent A Rock
+            opponent B Paper
+            opponent C Scissors
+
+            response X Lose
+            response Y Draw
+            response Z Win
+        "})
+        .unwrap();
+        assert_eq!(legend, Legend::part2());
+        assert_eq!(
+            legend.to_string(),
+            "A=Rock B=Paper C=Scissors X=Lose Y=Draw Z=Win"
+        );
+
+        // 相手の記号も凡例で決める
+        let legend = Legend::parse(indoc! {"
+            opponent 1 Scissors
+            opponent 2 Rock
+            opponent 3 Paper
+            response A Rock
+            response B Paper
+            response C Scissors
+        "})
+        .unwrap();
+        let guide = Guide::read("1 A\n2 B\n3 C\n".as_bytes()).unwrap();
+        // 1=Scissors vs Rock: 1+6, 2=Rock vs Paper: 2+6, 3=Paper vs Scissors: 3+6
+        assert_eq!(guide.score(&legend).unwrap(), 24);
+
+        let err = Legend::parse("opponent A Rock\nresponse X Rock\nresponse Y Win\n").unwrap_err();
+        assert_eq!(err.to_string(), "second column mixes shapes and outcomes");
+
+        let err = Legend::parse("opponent A Stone\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: opponent must be a shape: Stone");
+
+        let err = Legend::parse("opponent A Rock\nresponse X Stone\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: unknown meaning: Stone");
+
+        let err = Legend::parse("opponent A Rock\nopponent A Paper\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: duplicate symbol: A");
+
+        let err = Legend::parse("A Rock\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: illegal line: A Rock");
+
+        let err = Legend::parse("response X Rock\n").unwrap_err();
+        assert_eq!(err.to_string(), "no symbols for the first column");
+
+        let err = sample()
+            .score(&Legend::parse("opponent A Rock\nresponse X Rock\n").unwrap())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "unknown symbol: Y");
+    }
+
+    #[test]
+    fn test_search_mappings() {
+        let guide = sample();
+
+        let abc = Legend::part1().opponent;
+
+        let mappings = guide.search_mappings(&abc).unwrap();
+        assert_eq!(mappings.len(), 6);
+        assert_eq!(mappings[0], (Legend::part1(), 15));
+
+        let (legend, score) = guide.best_mapping(&abc).unwrap();
+        assert_eq!(score, 24);
+        assert_eq!(
+            legend.to_string(),
+            "A=Rock B=Paper C=Scissors X=Scissors Y=Paper Z=Rock"
+        );
+
+        let (legend, score) = guide.worst_mapping(&abc).unwrap();
+        assert_eq!(score, 6);
+        assert_eq!(
+            legend.to_string(),
+            "A=Rock B=Paper C=Scissors X=Rock Y=Scissors Z=Paper"
+        );
+
+        // 攻略本に出てくる2列目の記号だけを割り当てる
+        let guide = Guide::read("A P\nB Q\n".as_bytes()).unwrap();
+        let mappings = guide.search_mappings(&abc).unwrap();
+        assert_eq!(mappings.len(), 6);
+        assert_eq!(
+            mappings[0].0.to_string(),
+            "A=Rock B=Paper C=Scissors P=Rock Q=Paper"
+        );
+
+        let guide = Guide::read("A Z\nB Z\n".as_bytes()).unwrap();
+        assert_eq!(guide.search_mappings(&abc).unwrap().len(), 3);
+
+        let guide = Guide::read("A W\nA X\nA Y\nA Z\n".as_bytes()).unwrap();
+        let err = guide.search_mappings(&abc).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "4 symbols in the second column, but only 3 shapes"
+        );
+    }
+
+    #[test]
+    fn test_illegal_guide() {
+        let err = Guide::read("A Y\nB\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: illegal round: B");
+    }
+}
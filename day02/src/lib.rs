@@ -5,6 +5,17 @@ use nom::combinator::value;
 use nom::sequence::separated_pair;
 use nom::IResult;
 
+mod game;
+mod legend;
+mod tournament;
+
+pub use game::{get_shape, judge, Game, ShapeDef};
+pub use legend::{Guide, Legend, Response};
+pub use tournament::{
+    best_response, league, play_match, render_league, FixedSequence, FrequencyCounter, MatchResult,
+    PatternPredictor, RandomStrategy, Standing, Strategy,
+};
+
 /// グー、パー、チョキ
 #[derive(Debug, PartialEq, Clone)]
 pub enum Shape {
@@ -14,13 +25,27 @@ pub enum Shape {
 }
 
 impl Shape {
-    pub fn get_score(&self) -> u32 {
+    /// 組み込みのじゃんけん(`Game::rock_paper_scissors`)での番号
+    pub fn index(&self) -> usize {
         match self {
-            Shape::Rock => 1,
-            Shape::Paper => 2,
-            Shape::Scissors => 3,
+            Shape::Rock => 0,
+            Shape::Paper => 1,
+            Shape::Scissors => 2,
         }
     }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Shape::Rock),
+            1 => Some(Shape::Paper),
+            2 => Some(Shape::Scissors),
+            _ => None,
+        }
+    }
+
+    pub fn get_score(&self) -> u32 {
+        Game::rock_paper_scissors().get_score(self.index())
+    }
 }
 
 /// 勝敗
@@ -0,0 +1,373 @@
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use fastrand::Rng;
+
+use crate::{get_shape, judge, Outcome, Shape};
+
+/// じゃんけんの戦略。
+/// 自分と相手のこれまでの手を見て、次の手を決める。
+pub trait Strategy {
+    fn name(&self) -> String;
+
+    fn next_shape(&mut self, mine: &[Shape], theirs: &[Shape]) -> Shape;
+
+    /// 試合ごとに状態を初期化する
+    fn reset(&mut self) {}
+}
+
+/// 決まった手を順番に繰り返す
+pub struct FixedSequence {
+    name: String,
+    shapes: Vec<Shape>,
+}
+
+impl FixedSequence {
+    /// 手が1つもなければエラー
+    pub fn new(name: &str, shapes: Vec<Shape>) -> Result<Self> {
+        if shapes.is_empty() {
+            bail!("{}: empty sequence", name);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            shapes,
+        })
+    }
+}
+
+impl Strategy for FixedSequence {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn next_shape(&mut self, mine: &[Shape], _theirs: &[Shape]) -> Shape {
+        self.shapes[mine.len() % self.shapes.len()].clone()
+    }
+}
+
+/// 相手が一番よく出す手に勝つ手を出す
+pub struct FrequencyCounter;
+
+impl Strategy for FrequencyCounter {
+    fn name(&self) -> String {
+        "frequency".to_string()
+    }
+
+    fn next_shape(&mut self, _mine: &[Shape], theirs: &[Shape]) -> Shape {
+        get_shape(&most_frequent(theirs.iter()), &Outcome::Win)
+    }
+}
+
+/// 相手の直近 depth 手と同じ並びを過去から探し、その次に出た手を予測して勝つ手を出す。
+/// 見つからなければ、相手が一番よく出す手を予測する。
+pub struct PatternPredictor {
+    depth: usize,
+}
+
+impl PatternPredictor {
+    pub fn new(depth: usize) -> Self {
+        Self { depth }
+    }
+}
+
+impl Strategy for PatternPredictor {
+    fn name(&self) -> String {
+        format!("pattern{}", self.depth)
+    }
+
+    fn next_shape(&mut self, _mine: &[Shape], theirs: &[Shape]) -> Shape {
+        let prediction = if self.depth == 0 || theirs.len() <= self.depth {
+            most_frequent(theirs.iter())
+        } else {
+            let pattern = &theirs[theirs.len() - self.depth..];
+            let followers: Vec<&Shape> = theirs
+                .windows(self.depth + 1)
+                .filter(|w| &w[..self.depth] == pattern)
+                .map(|w| &w[self.depth])
+                .collect();
+
+            if followers.is_empty() {
+                most_frequent(theirs.iter())
+            } else {
+                most_frequent(followers.into_iter())
+            }
+        };
+
+        get_shape(&prediction, &Outcome::Win)
+    }
+}
+
+/// シード付きの乱数で手を出す
+pub struct RandomStrategy {
+    seed: u64,
+    rng: Rng,
+}
+
+impl RandomStrategy {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Rng::with_seed(seed),
+        }
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn name(&self) -> String {
+        format!("random{}", self.seed)
+    }
+
+    fn next_shape(&mut self, _mine: &[Shape], _theirs: &[Shape]) -> Shape {
+        Shape::from_index(self.rng.usize(..3)).unwrap()
+    }
+
+    fn reset(&mut self) {
+        self.rng = Rng::with_seed(self.seed);
+    }
+}
+
+/// 一番多く出た手。同数なら Rock, Paper, Scissors の順。なければ Rock。
+fn most_frequent<'a>(shapes: impl Iterator<Item = &'a Shape>) -> Shape {
+    let mut counts = [0; 3];
+    for shape in shapes {
+        counts[shape.index()] += 1;
+    }
+
+    let index = (0..3).rev().max_by_key(|&i| counts[i]).unwrap();
+    Shape::from_index(index).unwrap()
+}
+
+/// 1試合の結果
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MatchResult {
+    pub score: (u32, u32),
+    pub wins: (u32, u32),
+    pub draws: u32,
+}
+
+/// 2つの戦略を rounds 回対戦させる。点数は `Shape::get_score` + `Outcome::get_score`。
+pub fn play_match(a: &mut dyn Strategy, b: &mut dyn Strategy, rounds: usize) -> MatchResult {
+    a.reset();
+    b.reset();
+
+    let mut shapes_a = Vec::with_capacity(rounds);
+    let mut shapes_b = Vec::with_capacity(rounds);
+    let mut result = MatchResult::default();
+
+    for _ in 0..rounds {
+        let shape_a = a.next_shape(&shapes_a, &shapes_b);
+        let shape_b = b.next_shape(&shapes_b, &shapes_a);
+
+        let outcome_a = judge(&shape_b, &shape_a);
+        let outcome_b = judge(&shape_a, &shape_b);
+        result.score.0 += shape_a.get_score() + outcome_a.get_score();
+        result.score.1 += shape_b.get_score() + outcome_b.get_score();
+        match outcome_a {
+            Outcome::Win => result.wins.0 += 1,
+            Outcome::Lose => result.wins.1 += 1,
+            Outcome::Draw => result.draws += 1,
+        }
+
+        shapes_a.push(shape_a);
+        shapes_b.push(shape_b);
+    }
+
+    result
+}
+
+/// リーグ表の1行。試合の勝ちは3点、引き分けは1点。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Standing {
+    pub name: String,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub points: u32,
+    pub score: u32,
+}
+
+/// 総当たりで対戦させて、勝ち点、合計点の順に並べたリーグ表を返す。
+pub fn league(strategies: &mut [Box<dyn Strategy>], rounds: usize) -> Vec<Standing> {
+    let mut table: Vec<Standing> = strategies
+        .iter()
+        .map(|s| Standing {
+            name: s.name(),
+            ..Default::default()
+        })
+        .collect();
+
+    for i in 0..strategies.len() {
+        for j in (i + 1)..strategies.len() {
+            let (left, right) = strategies.split_at_mut(j);
+            let result = play_match(left[i].as_mut(), right[0].as_mut(), rounds);
+
+            table[i].score += result.score.0;
+            table[j].score += result.score.1;
+            match result.wins.0.cmp(&result.wins.1) {
+                std::cmp::Ordering::Greater => {
+                    table[i].wins += 1;
+                    table[j].losses += 1;
+                }
+                std::cmp::Ordering::Less => {
+                    table[j].wins += 1;
+                    table[i].losses += 1;
+                }
+                std::cmp::Ordering::Equal => {
+                    table[i].draws += 1;
+                    table[j].draws += 1;
+                }
+            }
+        }
+    }
+
+    for standing in table.iter_mut() {
+        standing.points = standing.wins * 3 + standing.draws;
+    }
+    table.sort_by(|a, b| {
+        b.points
+            .cmp(&a.points)
+            .then(b.score.cmp(&a.score))
+            .then(a.name.cmp(&b.name))
+    });
+
+    table
+}
+
+/// リーグ表を文字列にする
+pub fn render_league(table: &[Standing]) -> String {
+    let width = table.iter().map(|s| s.name.len()).max().unwrap_or(0).max(4);
+
+    let mut s = String::new();
+    writeln!(
+        s,
+        "{:<width$}  {:>3} {:>3} {:>3} {:>4} {:>8}",
+        "name", "W", "D", "L", "pts", "score"
+    )
+    .unwrap();
+    for standing in table {
+        writeln!(
+            s,
+            "{:<width$}  {:>3} {:>3} {:>3} {:>4} {:>8}",
+            standing.name,
+            standing.wins,
+            standing.draws,
+            standing.losses,
+            standing.points,
+            standing.score
+        )
+        .unwrap();
+    }
+    s
+}
+
+/// 相手の手が分かっているときの最適な手と、その合計点。
+/// ラウンドごとに独立なので、点数が最大になる手をそれぞれ選べばよい。
+pub fn best_response(opponent: &[Shape]) -> (Vec<Shape>, u32) {
+    let shapes: Vec<Shape> = opponent
+        .iter()
+        .map(|theirs| {
+            (0..3)
+                .map(|i| Shape::from_index(i).unwrap())
+                .max_by_key(|mine| mine.get_score() + judge(theirs, mine).get_score())
+                .unwrap()
+        })
+        .collect();
+
+    let score = opponent
+        .iter()
+        .zip(&shapes)
+        .map(|(theirs, mine)| mine.get_score() + judge(theirs, mine).get_score())
+        .sum();
+
+    (shapes, score)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use Shape::*;
+
+    use super::*;
+    use crate::{Guide, Legend};
+
+    #[test]
+    fn test_play_match() {
+        let mut rock = FixedSequence::new("rock", vec![Rock]).unwrap();
+        let mut cycle = FixedSequence::new("cycle", vec![Rock, Paper, Scissors]).unwrap();
+
+        let e = FixedSequence::new("none", vec![]).err().unwrap();
+        assert_eq!(e.to_string(), "none: empty sequence");
+
+        // R-R 引き分け, R-P 負け, R-S 勝ち
+        let result = play_match(&mut rock, &mut cycle, 3);
+        assert_eq!(
+            result,
+            MatchResult {
+                score: (1 + 3 + 1 + 1 + 6, 1 + 3 + 2 + 6 + 3),
+                wins: (1, 1),
+                draws: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_predictors() {
+        // 相手がRockしか出さなければ、Paperで勝ち続ける
+        let mut rock = FixedSequence::new("rock", vec![Rock]).unwrap();
+        let result = play_match(&mut FrequencyCounter, &mut rock, 10);
+        assert_eq!(result.wins, (10, 0));
+
+        // 周期的な相手には、パターンを覚えたら勝ち続ける
+        let mut cycle = FixedSequence::new("cycle", vec![Rock, Rock, Paper, Scissors]).unwrap();
+        let result = play_match(&mut PatternPredictor::new(2), &mut cycle, 100);
+        assert!(result.wins.0 >= 90, "{:?}", result);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut a = RandomStrategy::new(42);
+        let first: Vec<Shape> = (0..20).map(|_| a.next_shape(&[], &[])).collect();
+        a.reset();
+        let second: Vec<Shape> = (0..20).map(|_| a.next_shape(&[], &[])).collect();
+        assert_eq!(first, second);
+
+        for i in 0..3 {
+            assert!(first.iter().any(|s| s.index() == i));
+        }
+    }
+
+    #[test]
+    fn test_league() {
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(FixedSequence::new("rock", vec![Rock]).unwrap()),
+            Box::new(FixedSequence::new("paper", vec![Paper]).unwrap()),
+            Box::new(FrequencyCounter),
+        ];
+
+        let table = league(&mut strategies, 10);
+        assert_eq!(
+            render_league(&table),
+            indoc! {"
+                name         W   D   L  pts    score
+                frequency    2   0   0    6      166
+                paper        1   0   1    3      103
+                rock         0   0   2    0       20
+            "}
+        );
+    }
+
+    #[test]
+    fn test_best_response() {
+        let (shapes, score) = best_response(&[Rock, Paper, Scissors]);
+        assert_eq!(shapes, vec![Paper, Scissors, Rock]);
+        assert_eq!(score, 8 + 9 + 7);
+
+        assert_eq!(best_response(&[]), (vec![], 0));
+
+        let guide = Guide::read(include_str!("../data/sample.txt").as_bytes()).unwrap();
+        let opponent = guide.opponent_shapes(&Legend::part1()).unwrap();
+        assert_eq!(best_response(&opponent).1, 24);
+        assert_eq!(guide.best_mapping(&Legend::part1().opponent).unwrap().1, 24);
+    }
+}
@@ -1,25 +1,20 @@
-use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-use day03::{Priority, split_half};
+use day03::{split_half, Mode, Priority, Rucksack};
+use tools::input::input_path;
 
 /// 左と右で重複したアイテムを返す。
 /// ひとつだけ重複してることを期待する。
 fn find_duplicate_item_of_compartments(left: &str, right: &str) -> Result<char, String> {
-    // ハッシュセットにしてユニーク化
-    let left: HashSet<char> = HashSet::from_iter(left.chars());
-    let right: HashSet<char> = HashSet::from_iter(right.chars());
+    let left = Rucksack::new(left).map_err(|e| e.to_string())?;
+    let right = Rucksack::new(right).map_err(|e| e.to_string())?;
 
     // 重複したものだけを返す。
-    let duplicate_items: Vec<char> = left.intersection(&right).cloned().collect();
-
-    if duplicate_items.len() != 1 {
-        return Err(format!(
-            "expected only one item duplicated but them: {:?}",
-            duplicate_items
-        ));
-    }
+    let duplicate_items = left
+        .intersection(&right)
+        .shared_items(Mode::Strict)
+        .map_err(|e| e.to_string())?;
 
     Ok(duplicate_items[0])
 }
@@ -44,7 +39,8 @@ fn calc_part1(r: impl BufRead) -> u32 {
 }
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day03/data/input.txt")?);
+    let path = input_path("day03");
+    let r = BufReader::new(File::open(path)?);
     let total = calc_part1(r);
     println!("answer: {}", total);
     Ok(())
@@ -52,6 +48,8 @@ fn main() -> anyhow::Result<()> {
 
 #[cfg(test)]
 mod test {
+    use tools::answers::read_answers;
+
     use super::*;
 
     #[test]
@@ -75,6 +73,7 @@ mod test {
     fn test_sample1() {
         let r = include_str!("../../data/sample.txt").as_bytes();
         let total = calc_part1(r);
-        assert_eq!(total, 157);
+        let answers = read_answers(include_str!("../../data/sample.answers"));
+        assert_eq!(total.to_string(), answers[&1]);
     }
 }
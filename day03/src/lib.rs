@@ -1,3 +1,9 @@
+mod planner;
+mod rucksack;
+
+pub use planner::{plan, Exchange, Plan, Swap};
+pub use rucksack::{compartment_items, group_items, priority_sum, Mode, Rucksack};
+
 /// 文字列を半分に分割する。
 /// 文字数が偶数でなければNoneを返す。
 pub fn split_half(line: &str) -> Option<(&str, &str)> {
@@ -0,0 +1,488 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+use crate::{compartment_items, group_items, priority_sum, split_half, Mode, Priority};
+
+/// リュックサックの中の2つのアイテムの入れ替え。位置は行の中の文字の位置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Swap {
+    pub rucksack: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl Swap {
+    pub fn apply(&self, rucksacks: &mut [String]) {
+        let mut items: Vec<char> = rucksacks[self.rucksack].chars().collect();
+        items.swap(self.left, self.right);
+        rucksacks[self.rucksack] = items.into_iter().collect();
+    }
+}
+
+/// 2つのリュックサックの間でのアイテムの交換。(リュックサック, 行の中の文字の位置)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exchange {
+    pub first: (usize, usize),
+    pub second: (usize, usize),
+}
+
+impl Exchange {
+    pub fn apply(&self, rucksacks: &mut [String]) {
+        let (i, p) = self.first;
+        let (j, q) = self.second;
+        let mut first: Vec<char> = rucksacks[i].chars().collect();
+        let mut second: Vec<char> = rucksacks[j].chars().collect();
+        std::mem::swap(&mut first[p], &mut second[q]);
+        rucksacks[i] = first.into_iter().collect();
+        rucksacks[j] = second.into_iter().collect();
+    }
+}
+
+/// 並べ直しの計画。exchanges の後に swaps を行う。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub exchanges: Vec<(Exchange, char, char)>,
+    pub swaps: Vec<(Swap, char, char)>,
+    /// 前後のコンパートメントで重複しているアイテムのプライオリティの合計
+    pub before: u32,
+    pub after: u32,
+    /// グループ(3人)のバッジのプライオリティの合計
+    pub badges_before: u32,
+    pub badges_after: u32,
+    /// 交換と入れ替えをしても重複をなくせなかったリュックサック
+    pub unresolved: Vec<usize>,
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (exchange, first, second) in &self.exchanges {
+            writeln!(
+                f,
+                "rucksack {} <-> {}: exchange {}@{} <-> {}@{}",
+                exchange.first.0 + 1,
+                exchange.second.0 + 1,
+                first,
+                exchange.first.1,
+                second,
+                exchange.second.1
+            )?;
+        }
+        for (swap, left, right) in &self.swaps {
+            writeln!(
+                f,
+                "rucksack {}: swap {}@{} <-> {}@{}",
+                swap.rucksack + 1,
+                left,
+                swap.left,
+                right,
+                swap.right
+            )?;
+        }
+        writeln!(f, "duplicates: {} -> {}", self.before, self.after)?;
+        writeln!(f, "badges: {} -> {}", self.badges_before, self.badges_after)?;
+        if !self.unresolved.is_empty() {
+            let unresolved: Vec<String> = self
+                .unresolved
+                .iter()
+                .map(|i| (i + 1).to_string())
+                .collect();
+            writeln!(f, "unresolved: {}", unresolved.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// 前後のコンパートメントで重複するアイテムがなくなるように、入れ替えの計画を立てる。
+///
+/// 種類ごとの個数を前半と後半にちょうど半分ずつ分けられないリュックサックは、
+/// まず他のリュックサックとアイテムを1つずつ交換して分けられるようにする(exchanges)。
+/// 交換しても大きさは変わらず、グループの共通アイテム(バッジ)が変わる交換は選ばない。
+/// 交換は、分け方の半分からのずれが一番小さくなるものを1つずつ貪欲に選ぶ。
+///
+/// その後、各リュックサックの中で、前半に集めるアイテムの種類をナップサックで選び、
+/// 入れ替えの回数(前半に残っている後半行きのアイテムの数)を最小にする(swaps)。
+pub fn plan(rucksacks: &[impl AsRef<str>]) -> Result<Plan> {
+    let lines: Vec<String> = rucksacks.iter().map(|r| r.as_ref().to_string()).collect();
+
+    let before = duplicate_total(&lines)?;
+    let badges_before = badge_total(&lines)?;
+
+    let mut after_lines = lines.clone();
+    let exchanges = plan_exchanges(&mut after_lines)?;
+
+    let mut swaps = Vec::new();
+    let mut unresolved = Vec::new();
+    for index in 0..after_lines.len() {
+        match plan_rucksack(index, &after_lines[index])? {
+            Some(rucksack_swaps) => {
+                for swap in rucksack_swaps {
+                    let items: Vec<char> = after_lines[index].chars().collect();
+                    swaps.push((swap, items[swap.left], items[swap.right]));
+                    swap.apply(&mut after_lines);
+                }
+            }
+            None => unresolved.push(index),
+        }
+    }
+
+    Ok(Plan {
+        exchanges,
+        swaps,
+        before,
+        after: duplicate_total(&after_lines)?,
+        badges_before,
+        badges_after: badge_total(&after_lines)?,
+        unresolved,
+    })
+}
+
+/// 半分に分けられないリュックサックを、他のリュックサックとの交換で分けられるようにする。
+/// lines は交換した後の並びになる。
+fn plan_exchanges(lines: &mut [String]) -> Result<Vec<(Exchange, char, char)>> {
+    let halves: Vec<usize> = lines
+        .iter()
+        .map(|line| {
+            split_half(line)
+                .map(|(left, _)| left.chars().count())
+                .ok_or_else(|| anyhow!("expected even length: {}", line))
+        })
+        .collect::<Result<_>>()?;
+    let mut counts: Vec<[usize; 53]> = lines
+        .iter()
+        .map(|line| item_counts(line))
+        .collect::<Result<_>>()?;
+    let mut masks: Vec<u64> = counts.iter().map(item_mask).collect();
+    // グループの人数がそろわないときは、バッジがないので気にしない
+    let grouped = lines.len().is_multiple_of(3);
+
+    let mut exchanges = Vec::new();
+    for i in 0..lines.len() {
+        let mut distance = split_distance(&counts[i], halves[i]);
+        while distance > 0 {
+            // (ずれ, 相手, 出す種類, もらう種類)
+            let mut best: Option<(usize, usize, usize, usize)> = None;
+            'search: for j in (0..lines.len()).filter(|&j| j != i) {
+                let current_j = split_distance(&counts[j], halves[j]);
+                for x in (1..=52).filter(|&x| counts[i][x] > 0) {
+                    for y in (1..=52).filter(|&y| y != x && counts[j][y] > 0) {
+                        let mut new_i = counts[i];
+                        new_i[x] -= 1;
+                        new_i[y] += 1;
+                        let d = split_distance(&new_i, halves[i]);
+                        if best.map_or(distance, |b| b.0) <= d {
+                            continue;
+                        }
+
+                        let mut new_j = counts[j];
+                        new_j[y] -= 1;
+                        new_j[x] += 1;
+                        if split_distance(&new_j, halves[j]) > current_j {
+                            continue;
+                        }
+                        // 交換した2人のグループのバッジが変わらないこと
+                        let mask = |k: usize| match k {
+                            k if k == i => item_mask(&new_i),
+                            k if k == j => item_mask(&new_j),
+                            k => masks[k],
+                        };
+                        if grouped
+                            && [i / 3, j / 3]
+                                .iter()
+                                .any(|&g| group_badge(g, &mask) != group_badge(g, &|k| masks[k]))
+                        {
+                            continue;
+                        }
+
+                        best = Some((d, j, x, y));
+                        if d == 0 {
+                            break 'search;
+                        }
+                    }
+                }
+            }
+
+            let Some((d, j, x, y)) = best else {
+                break;
+            };
+            let p = position(&lines[i], x)?;
+            let q = position(&lines[j], y)?;
+            let exchange = Exchange {
+                first: (i, p),
+                second: (j, q),
+            };
+            let first = lines[i].chars().nth(p).unwrap();
+            let second = lines[j].chars().nth(q).unwrap();
+            exchanges.push((exchange, first, second));
+            exchange.apply(lines);
+
+            counts[i][x] -= 1;
+            counts[i][y] += 1;
+            counts[j][y] -= 1;
+            counts[j][x] += 1;
+            masks[i] = item_mask(&counts[i]);
+            masks[j] = item_mask(&counts[j]);
+            distance = d;
+        }
+    }
+
+    Ok(exchanges)
+}
+
+/// プライオリティごとの個数
+fn item_counts(line: &str) -> Result<[usize; 53]> {
+    let mut counts = [0; 53];
+    for item in line.chars() {
+        counts[priority(item)?] += 1;
+    }
+    Ok(counts)
+}
+
+/// 入っているアイテムの種類のマスク
+fn item_mask(counts: &[usize; 53]) -> u64 {
+    (1..=52)
+        .filter(|&p| counts[p] > 0)
+        .fold(0, |mask, p| mask | 1 << p)
+}
+
+/// g番目のグループ(3人)の共通アイテムのマスク
+fn group_badge(g: usize, mask: &impl Fn(usize) -> u64) -> u64 {
+    (g * 3..g * 3 + 3).fold(u64::MAX, |badge, k| badge & mask(k))
+}
+
+/// 種類ごとに前半か後半に振り分けたときの、前半の個数と half のずれの最小値
+fn split_distance(counts: &[usize; 53], half: usize) -> usize {
+    let total: usize = counts.iter().sum();
+    let mut reachable = vec![false; total + 1];
+    reachable[0] = true;
+    for &count in counts.iter().filter(|&&c| c > 0) {
+        for k in (count..=total).rev() {
+            reachable[k] |= reachable[k - count];
+        }
+    }
+    (0..=total)
+        .filter(|&k| reachable[k])
+        .map(|k| k.abs_diff(half))
+        .min()
+        .unwrap_or(0)
+}
+
+/// プライオリティがpのアイテムの、行の中の最後の位置
+fn position(line: &str, p: usize) -> Result<usize> {
+    let items: Vec<char> = line.chars().collect();
+    for (i, &item) in items.iter().enumerate().rev() {
+        if priority(item)? == p {
+            return Ok(i);
+        }
+    }
+    Err(anyhow!("no item of priority {} in {}", p, line))
+}
+
+/// 1つのリュックサックの入れ替え。重複をなくせなければNone。
+fn plan_rucksack(index: usize, line: &str) -> Result<Option<Vec<Swap>>> {
+    let (left, right) =
+        split_half(line).ok_or_else(|| anyhow!("expected even length: {}", line))?;
+    let half = left.chars().count();
+
+    // プライオリティごとの前半と後半の個数
+    let mut counts = [(0, 0); 53];
+    for item in left.chars() {
+        counts[priority(item)?].0 += 1;
+    }
+    for item in right.chars() {
+        counts[priority(item)?].1 += 1;
+    }
+
+    // dp[k] = 合計k個になるように種類を選んだときの、前半に元からある個数の最大
+    let types: Vec<usize> = (1..=52).filter(|&p| counts[p] != (0, 0)).collect();
+    let mut dp: Vec<Option<usize>> = vec![None; half + 1];
+    let mut chosen: Vec<Vec<bool>> = vec![vec![false; half + 1]; types.len()];
+    dp[0] = Some(0);
+    for (t, &p) in types.iter().enumerate() {
+        let (l, r) = counts[p];
+        let total = l + r;
+        for k in (total..=half).rev() {
+            if let Some(base) = dp[k - total] {
+                if dp[k].is_none_or(|current| base + l > current) {
+                    dp[k] = Some(base + l);
+                    chosen[t][k] = true;
+                }
+            }
+        }
+    }
+
+    if dp[half].is_none() {
+        return Ok(None);
+    }
+
+    // 前半に集める種類を復元
+    let mut to_left = [false; 53];
+    let mut k = half;
+    for (t, &p) in types.iter().enumerate().rev() {
+        if chosen[t][k] {
+            to_left[p] = true;
+            k -= counts[p].0 + counts[p].1;
+        }
+    }
+
+    let items: Vec<char> = line.chars().collect();
+    let misplaced_left = (0..half).filter(|&i| !to_left[priority(items[i]).unwrap()]);
+    let misplaced_right = (half..items.len()).filter(|&i| to_left[priority(items[i]).unwrap()]);
+
+    Ok(Some(
+        misplaced_left
+            .zip(misplaced_right)
+            .map(|(left, right)| Swap {
+                rucksack: index,
+                left,
+                right,
+            })
+            .collect(),
+    ))
+}
+
+fn priority(item: char) -> Result<usize> {
+    item.to_priority()
+        .map(|p| p as usize)
+        .ok_or_else(|| anyhow!("illegal item: {:?}", item))
+}
+
+fn duplicate_total(lines: &[String]) -> Result<u32> {
+    lines
+        .iter()
+        .map(|line| Ok(priority_sum(&compartment_items(line, Mode::Lenient)?)))
+        .sum()
+}
+
+fn badge_total(lines: &[String]) -> Result<u32> {
+    if !lines.len().is_multiple_of(3) {
+        return Ok(0);
+    }
+    Ok(group_items(lines, 3, Mode::Lenient)?
+        .iter()
+        .map(|items| priority_sum(items))
+        .sum())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> Vec<String> {
+        include_str!("../data/sample.txt")
+            .lines()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    /// 計画どおりに交換と入れ替えをした後の並び
+    fn apply(lines: &[&str], plan: &Plan) -> Vec<String> {
+        let mut after: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
+        for (exchange, _, _) in &plan.exchanges {
+            exchange.apply(&mut after);
+        }
+        for (swap, _, _) in &plan.swaps {
+            swap.apply(&mut after);
+        }
+        after
+    }
+
+    #[test]
+    fn test_plan_sample() {
+        let lines = sample();
+        let plan = plan(&lines).unwrap();
+        assert!(plan.exchanges.is_empty());
+
+        assert_eq!(plan.before, 157);
+        assert_eq!(plan.after, 0);
+        assert_eq!(plan.badges_before, 70);
+        assert_eq!(plan.badges_after, 70);
+        assert!(plan.unresolved.is_empty());
+
+        // 計画どおりに入れ替えると重複がなくなり、大きさは変わらない
+        let mut after = lines.clone();
+        for (swap, _, _) in &plan.swaps {
+            swap.apply(&mut after);
+        }
+        for (before, after) in lines.iter().zip(&after) {
+            assert_eq!(before.len(), after.len());
+            assert_eq!(compartment_items(after, Mode::Lenient).unwrap(), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_plan_minimal() {
+        // "ab|ba" は1回の入れ替えで "aa|bb" になる
+        let result = plan(&["abba"]).unwrap();
+        assert_eq!(
+            result.swaps,
+            vec![(
+                Swap {
+                    rucksack: 0,
+                    left: 1,
+                    right: 3
+                },
+                'b',
+                'a'
+            )]
+        );
+        assert_eq!(
+            result.to_string(),
+            "rucksack 1: swap b@1 <-> a@3\nduplicates: 3 -> 0\nbadges: 0 -> 0\n"
+        );
+
+        // 重複がなければ何もしない
+        let result = plan(&["abcd"]).unwrap();
+        assert!(result.swaps.is_empty());
+
+        // aが4個あるので、3個ずつに分けられない。交換する相手もいない
+        let result = plan(&["aaabba"]).unwrap();
+        assert!(result.exchanges.is_empty());
+        assert_eq!(result.unresolved, vec![0]);
+        assert_eq!(result.after, result.before);
+    }
+
+    #[test]
+    fn test_plan_exchange() {
+        // 1人目は中の入れ替えだけでは分けられないので、2人目とaを交換する。
+        // bと交換すると2人目からバッジのbがなくなるので、cと交換する。
+        let lines = ["aaabba", "bcdefg", "bvwxyz"];
+        let result = plan(&lines).unwrap();
+        assert_eq!(
+            result.exchanges,
+            vec![(
+                Exchange {
+                    first: (0, 5),
+                    second: (1, 1)
+                },
+                'a',
+                'c'
+            )]
+        );
+        assert!(result.unresolved.is_empty());
+        assert_eq!(result.after, 0);
+        assert_eq!(result.badges_before, 2);
+        assert_eq!(result.badges_after, 2);
+        assert!(result
+            .to_string()
+            .starts_with("rucksack 1 <-> 2: exchange a@5 <-> c@1\n"));
+
+        let after = apply(&lines, &result);
+        assert_eq!(after, vec!["aaabbc", "badefg", "bvwxyz"]);
+        for (before, after) in lines.iter().zip(&after) {
+            assert_eq!(before.len(), after.len());
+            assert_eq!(compartment_items(after, Mode::Lenient).unwrap(), vec![]);
+        }
+
+        // 1回の交換では足りないときは、ずれが小さくなる交換を続ける
+        let lines = ["aaaaaa", "bcdefg"];
+        let result = plan(&lines).unwrap();
+        assert_eq!(result.exchanges.len(), 3);
+        assert!(result.unresolved.is_empty());
+        assert_eq!(result.after, 0);
+        for after in apply(&lines, &result) {
+            assert_eq!(after.len(), 6);
+            assert_eq!(compartment_items(&after, Mode::Lenient).unwrap(), vec![]);
+        }
+    }
+}
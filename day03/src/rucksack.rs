@@ -0,0 +1,211 @@
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::{split_half, Priority};
+
+/// 共通アイテムの扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// 共通アイテムをすべて返す
+    Lenient,
+    /// 共通アイテムがちょうど1つでなければエラー
+    Strict,
+}
+
+/// リュックサックの中身。プライオリティ値をビット位置にした52ビットのマスクで持つ。
+/// 同じアイテムがいくつあっても1つとして扱う。
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rucksack {
+    mask: u64,
+}
+
+impl Rucksack {
+    /// a-zA-Z 以外のアイテムがあればエラー
+    pub fn new(items: &str) -> Result<Self> {
+        let mut rucksack = Self::default();
+        for item in items.chars() {
+            let priority = item
+                .to_priority()
+                .ok_or_else(|| anyhow!("illegal item: {:?}", item))?;
+            rucksack.mask |= 1 << priority;
+        }
+        Ok(rucksack)
+    }
+
+    /// 前半と後半のコンパートメント
+    pub fn compartments(line: &str) -> Result<(Self, Self)> {
+        let (left, right) =
+            split_half(line).ok_or_else(|| anyhow!("expected even length: {}", line))?;
+        Ok((Self::new(left)?, Self::new(right)?))
+    }
+
+    pub fn contains(&self, item: char) -> bool {
+        item.to_priority()
+            .is_some_and(|priority| self.mask & (1 << priority) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.mask.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            mask: self.mask & other.mask,
+        }
+    }
+
+    /// すべてのリュックサックに共通するアイテム。空なら空のリュックサック。
+    pub fn intersect_all<'a>(rucksacks: impl IntoIterator<Item = &'a Self>) -> Self {
+        let mut rucksacks = rucksacks.into_iter();
+        let Some(first) = rucksacks.next() else {
+            return Self::default();
+        };
+        rucksacks.fold(*first, |acc, r| acc.intersection(r))
+    }
+
+    /// アイテムをプライオリティの順に返す
+    pub fn items(&self) -> Vec<char> {
+        (1..=52)
+            .filter(|priority| self.mask & (1 << priority) != 0)
+            .map(|priority| from_priority(priority).unwrap())
+            .collect()
+    }
+
+    pub fn priority_sum(&self) -> u32 {
+        (1..=52)
+            .filter(|priority| self.mask & (1 << priority) != 0)
+            .sum()
+    }
+
+    /// モードに従って共通アイテムを返す
+    pub fn shared_items(&self, mode: Mode) -> Result<Vec<char>> {
+        let items = self.items();
+        if mode == Mode::Strict && items.len() != 1 {
+            bail!("expected only one item duplicated but them: {:?}", items);
+        }
+        Ok(items)
+    }
+}
+
+impl fmt::Debug for Rucksack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rucksack({})", self.items().iter().collect::<String>())
+    }
+}
+
+fn from_priority(priority: u32) -> Option<char> {
+    match priority {
+        1..=26 => char::from_u32(u32::from('a') + priority - 1),
+        27..=52 => char::from_u32(u32::from('A') + priority - 27),
+        _ => None,
+    }
+}
+
+/// 前半と後半の両方に入っているアイテム
+pub fn compartment_items(line: &str, mode: Mode) -> Result<Vec<char>> {
+    let (left, right) = Rucksack::compartments(line)?;
+    left.intersection(&right).shared_items(mode)
+}
+
+/// group_size 行ずつのグループに共通するアイテム(バッジ)
+pub fn group_items(
+    lines: &[impl AsRef<str>],
+    group_size: usize,
+    mode: Mode,
+) -> Result<Vec<Vec<char>>> {
+    if group_size == 0 || !lines.len().is_multiple_of(group_size) {
+        bail!(
+            "{} lines can not be split into groups of {}",
+            lines.len(),
+            group_size
+        );
+    }
+
+    lines
+        .chunks(group_size)
+        .map(|group| {
+            let rucksacks = group
+                .iter()
+                .map(|line| Rucksack::new(line.as_ref()))
+                .collect::<Result<Vec<_>>>()?;
+            Rucksack::intersect_all(&rucksacks).shared_items(mode)
+        })
+        .collect()
+}
+
+/// アイテムのプライオリティの合計
+pub fn priority_sum(items: &[char]) -> u32 {
+    items.iter().filter_map(|item| item.to_priority()).sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> Vec<&'static str> {
+        include_str!("../data/sample.txt").lines().collect()
+    }
+
+    #[test]
+    fn test_rucksack() {
+        let rucksack = Rucksack::new("abcaZ").unwrap();
+        assert_eq!(rucksack.len(), 4);
+        assert!(rucksack.contains('Z'));
+        assert!(!rucksack.contains('z'));
+        assert_eq!(rucksack.items(), vec!['a', 'b', 'c', 'Z']);
+        assert_eq!(rucksack.priority_sum(), 1 + 2 + 3 + 52);
+        assert_eq!(format!("{:?}", rucksack), "Rucksack(abcZ)");
+
+        assert!(Rucksack::new("ab1").is_err());
+        assert!(Rucksack::compartments("abc").is_err());
+    }
+
+    #[test]
+    fn test_intersect_all() {
+        let rucksacks = ["abcd", "bcde", "cdef", "cxd"].map(|s| Rucksack::new(s).unwrap());
+        assert_eq!(Rucksack::intersect_all(&rucksacks).items(), vec!['c', 'd']);
+        assert_eq!(Rucksack::intersect_all(&rucksacks[..1]), rucksacks[0]);
+        assert!(Rucksack::intersect_all(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_compartment_items() {
+        let total: u32 = sample()
+            .iter()
+            .map(|line| priority_sum(&compartment_items(line, Mode::Strict).unwrap()))
+            .sum();
+        assert_eq!(total, 157);
+
+        assert_eq!(
+            compartment_items("abcabd", Mode::Lenient).unwrap(),
+            vec!['a', 'b']
+        );
+        assert!(compartment_items("abcabd", Mode::Strict).is_err());
+        assert_eq!(compartment_items("abcdef", Mode::Lenient).unwrap(), vec![]);
+        assert!(compartment_items("abcdef", Mode::Strict).is_err());
+    }
+
+    #[test]
+    fn test_group_items() {
+        let badges = group_items(&sample(), 3, Mode::Strict).unwrap();
+        assert_eq!(badges, vec![vec!['r'], vec!['Z']]);
+        assert_eq!(badges.iter().map(|b| priority_sum(b)).sum::<u32>(), 70);
+
+        // 6人で1グループ
+        let badges = group_items(&sample(), 6, Mode::Lenient).unwrap();
+        assert_eq!(badges, vec![vec![]]);
+        assert!(group_items(&sample(), 6, Mode::Strict).is_err());
+
+        // 2人ずつ
+        let badges = group_items(&sample(), 2, Mode::Lenient).unwrap();
+        assert_eq!(badges.len(), 3);
+
+        assert!(group_items(&sample(), 4, Mode::Lenient).is_err());
+        assert!(group_items(&sample(), 0, Mode::Lenient).is_err());
+    }
+}
@@ -3,6 +3,7 @@ use std::io::BufReader;
 use std::ops::RangeInclusive;
 
 use day04::read_list;
+use tools::input::input_path;
 
 fn overlap_contains(pair: &(RangeInclusive<u32>, RangeInclusive<u32>)) -> bool {
     let (a, b) = pair;
@@ -16,7 +17,8 @@ fn overlap_contains(pair: &(RangeInclusive<u32>, RangeInclusive<u32>)) -> bool {
 }
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day04/data/input.txt")?);
+    let path = input_path("day04");
+    let r = BufReader::new(File::open(path)?);
 
     let count = read_list(r)?.into_iter().filter(overlap_contains).count();
     println!("answer: {}", count);
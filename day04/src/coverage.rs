@@ -0,0 +1,256 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+type Pair = (RangeInclusive<u32>, RangeInclusive<u32>);
+
+/// リスト全体で、区画がどのエルフに担当されているかの分析。
+/// エルフの番号は、ペアの左右の順に0から振る(n行目の左が 2n、右が 2n+1)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub elves: Vec<RangeInclusive<u32>>,
+    /// 最初から最後の区画の間で、誰も担当していない区画
+    pub uncovered: Vec<RangeInclusive<u32>>,
+    /// 2人以上が担当している区画と、その人数
+    pub multiply_covered: Vec<(RangeInclusive<u32>, usize)>,
+    /// 担当する区画がすべて他のエルフとも重なっているエルフ
+    pub redundant: Vec<usize>,
+    /// 同じ区画を担当できる最小のエルフの組み合わせ
+    pub minimal_cover: Vec<usize>,
+}
+
+impl CoverageReport {
+    pub fn analyze(pairs: &[Pair]) -> Self {
+        let elves: Vec<RangeInclusive<u32>> = pairs
+            .iter()
+            .flat_map(|(a, b)| [a.clone(), b.clone()])
+            .collect();
+
+        let segments = segments(&elves);
+
+        let uncovered = segments
+            .iter()
+            .filter(|(_, count)| *count == 0)
+            .map(|(range, _)| range.clone())
+            .collect();
+
+        let multiply_covered = segments
+            .iter()
+            .filter(|(_, count)| *count >= 2)
+            .cloned()
+            .collect();
+
+        let redundant = elves
+            .iter()
+            .enumerate()
+            .filter(|(_, elf)| {
+                segments
+                    .iter()
+                    .filter(|(range, _)| range.start() <= elf.end() && elf.start() <= range.end())
+                    .all(|(_, count)| *count >= 2)
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        let minimal_cover = minimal_cover(&elves);
+
+        Self {
+            elves,
+            uncovered,
+            multiply_covered,
+            redundant,
+            minimal_cover,
+        }
+    }
+
+    /// 誰かが担当している区画の数
+    pub fn covered_sections(&self) -> u64 {
+        let covered: u64 = segments(&self.elves)
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(range, _)| (range.end() - range.start()) as u64 + 1)
+            .sum();
+        covered
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "elves: {}", self.elves.len())?;
+        writeln!(f, "covered sections: {}", self.covered_sections())?;
+        writeln!(f, "uncovered: {}", join_ranges(self.uncovered.iter()))?;
+        writeln!(
+            f,
+            "multiply covered: {}",
+            join(self.multiply_covered.iter().map(|(range, count)| format!(
+                "{}(x{})",
+                format_range(range),
+                count
+            )))
+        )?;
+        writeln!(
+            f,
+            "redundant: {}",
+            join(self.redundant.iter().map(|&i| self.format_elf(i)))
+        )?;
+        writeln!(
+            f,
+            "minimal cover ({}): {}",
+            self.minimal_cover.len(),
+            join(self.minimal_cover.iter().map(|&i| self.format_elf(i)))
+        )
+    }
+}
+
+impl CoverageReport {
+    fn format_elf(&self, index: usize) -> String {
+        format!("#{} {}", index, format_range(&self.elves[index]))
+    }
+}
+
+fn format_range(range: &RangeInclusive<u32>) -> String {
+    if range.start() == range.end() {
+        range.start().to_string()
+    } else {
+        format!("{}-{}", range.start(), range.end())
+    }
+}
+
+fn join_ranges<'a>(ranges: impl Iterator<Item = &'a RangeInclusive<u32>>) -> String {
+    join(ranges.map(format_range))
+}
+
+fn join(words: impl Iterator<Item = String>) -> String {
+    let words: Vec<String> = words.collect();
+    if words.is_empty() {
+        "-".to_string()
+    } else {
+        words.join(", ")
+    }
+}
+
+/// 最初から最後の区画までを、担当人数が同じ区間に分ける。
+fn segments(elves: &[RangeInclusive<u32>]) -> Vec<(RangeInclusive<u32>, usize)> {
+    // (位置, 人数の増減)。終わりは次の区画で減らす。
+    let mut events: Vec<(u64, i64)> = elves
+        .iter()
+        .filter(|elf| !elf.is_empty())
+        .flat_map(|elf| [(*elf.start() as u64, 1), (*elf.end() as u64 + 1, -1)])
+        .collect();
+    events.sort();
+
+    let mut segments: Vec<(RangeInclusive<u32>, usize)> = Vec::new();
+    let mut count: i64 = 0;
+    for i in 0..events.len() {
+        let (pos, delta) = events[i];
+        count += delta;
+
+        let Some(&(next, _)) = events.get(i + 1) else {
+            break;
+        };
+        if next == pos {
+            continue;
+        }
+
+        let range = pos as u32..=(next - 1) as u32;
+        match segments.last_mut() {
+            // 人数が同じなら続ける
+            Some((last, last_count))
+                if *last_count == count as usize && *last.end() as u64 + 1 == pos =>
+            {
+                *last = *last.start()..=*range.end();
+            }
+            _ => segments.push((range, count as usize)),
+        }
+    }
+
+    segments
+}
+
+/// 区間被覆の貪欲法。今の位置から始められるエルフのうち、一番遠くまで担当するものを選ぶ。
+fn minimal_cover(elves: &[RangeInclusive<u32>]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..elves.len()).filter(|&i| !elves[i].is_empty()).collect();
+    order.sort_by_key(|&i| (*elves[i].start(), std::cmp::Reverse(*elves[i].end())));
+
+    let mut cover = Vec::new();
+    let mut i = 0;
+    // 次に担当が必要な区画
+    let mut next: Option<u64> = None;
+    while i < order.len() {
+        let start = *elves[order[i]].start() as u64;
+        let from = match next {
+            Some(next) if start <= next => next,
+            _ => start,
+        };
+
+        // fromから始められる中で一番遠くまで届くもの
+        let mut best: Option<usize> = None;
+        while i < order.len() && *elves[order[i]].start() as u64 <= from {
+            let candidate = order[i];
+            if best.is_none_or(|b| elves[candidate].end() > elves[b].end()) {
+                best = Some(candidate);
+            }
+            i += 1;
+        }
+
+        let best = best.unwrap();
+        let end = *elves[best].end() as u64;
+        if end >= from {
+            cover.push(best);
+            next = Some(end + 1);
+        }
+    }
+
+    cover.sort();
+    cover
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::read_list;
+
+    #[test]
+    fn test_analyze_sample() {
+        let list = read_list(include_str!("../data/sample.txt").as_bytes()).unwrap();
+        let report = CoverageReport::analyze(&list);
+
+        assert_eq!(report.elves.len(), 12);
+        assert_eq!(report.uncovered, vec![]);
+        assert_eq!(report.minimal_cover, vec![5, 6]);
+        assert_eq!(
+            report.to_string(),
+            indoc! {"
+                elves: 12
+                covered sections: 8
+                uncovered: -
+                multiply covered: 2(x4), 3(x5), 4-5(x7), 6(x8), 7(x6), 8(x4)
+                redundant: #0 2-4, #1 6-8, #2 2-3, #3 4-5, #4 5-7, #6 2-8, #7 3-7, #8 6, #9 4-6, #10 2-6, #11 4-8
+                minimal cover (2): #5 7-9, #6 2-8
+            "}
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let report = CoverageReport::analyze(&[(1..=3, 2..=4), (8..=9, 2..=2)]);
+
+        assert_eq!(report.uncovered, vec![5..=7]);
+        assert_eq!(report.multiply_covered, vec![(2..=2, 3), (3..=3, 2)]);
+        // 2-2 は 1-3 と 2-4 の両方に含まれる
+        assert_eq!(report.redundant, vec![3]);
+        assert_eq!(report.minimal_cover, vec![0, 1, 2]);
+        assert_eq!(report.covered_sections(), 6);
+
+        // 空の担当(5-3)があっても番号はずれない
+        let list = read_list("5-3,1-2".as_bytes()).unwrap();
+        let report = CoverageReport::analyze(&list);
+        assert_eq!(report.minimal_cover, vec![1]);
+        assert_eq!(report.uncovered, vec![]);
+
+        let report = CoverageReport::analyze(&[]);
+        assert_eq!(report.minimal_cover, vec![]);
+        assert_eq!(report.covered_sections(), 0);
+    }
+}
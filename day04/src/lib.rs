@@ -7,6 +7,10 @@ use nom::combinator::map;
 use nom::sequence::separated_pair;
 use nom::IResult;
 
+mod coverage;
+
+pub use coverage::CoverageReport;
+
 fn parse_pair(input: &str) -> IResult<&str, (RangeInclusive<u32>, RangeInclusive<u32>)> {
     let range = |i| {
         map(
@@ -0,0 +1,362 @@
+use crate::error::OperationError;
+use crate::operation::Operation;
+use crate::stack::{render_stacks, Stacks};
+
+/// クレーンの機種。新しい機種はこのトレイトを実装して追加する。
+/// move_crates に渡す操作は、Crane が検証してから渡す。
+pub trait Model {
+    fn name(&self) -> &str;
+
+    fn move_crates(&self, stacks: &mut Stacks, op: &Operation);
+}
+
+/// CrateMover 9000: 1つずつ運ぶので、順番が逆になる
+pub struct CrateMover9000;
+
+impl Model for CrateMover9000 {
+    fn name(&self) -> &str {
+        "CrateMover 9000"
+    }
+
+    fn move_crates(&self, stacks: &mut Stacks, op: &Operation) {
+        // 移動元のスタック
+        let stack = &mut stacks[(op.from - 1) as usize];
+
+        // 末尾から指定個数分だけ取り出し、逆順にしておく。
+        let drained: Vec<String> = stack
+            .drain((stack.len() - op.times as usize)..)
+            .rev()
+            .collect();
+
+        // 移動先のスタックに追加
+        stacks[(op.to - 1) as usize].extend(drained);
+    }
+}
+
+/// CrateMover 9001: まとめて運ぶので、順番はそのまま
+pub struct CrateMover9001;
+
+impl Model for CrateMover9001 {
+    fn name(&self) -> &str {
+        "CrateMover 9001"
+    }
+
+    fn move_crates(&self, stacks: &mut Stacks, op: &Operation) {
+        // 移動元のスタック
+        let stack = &mut stacks[(op.from - 1) as usize];
+
+        // 末尾から指定個数分だけ取り出し、順番はそのまま。
+        let drained: Vec<String> = stack.drain((stack.len() - op.times as usize)..).collect();
+
+        // 移動先のスタックに追加
+        stacks[(op.to - 1) as usize].extend(drained);
+    }
+}
+
+/// 操作を1つずつ適用するクレーン。
+/// 各ステップのスタックを履歴として持ち、undo/redoできる。
+pub struct Crane {
+    model: Box<dyn Model>,
+    // history[i] は i 個の操作を適用した後のスタック
+    history: Vec<Stacks>,
+    operations: Vec<Operation>,
+    // 今のステップ。undoすると戻り、redoすると進む。
+    step: usize,
+}
+
+impl Crane {
+    pub fn new(model: impl Model + 'static, stacks: Stacks) -> Self {
+        Self {
+            model: Box::new(model),
+            history: vec![stacks],
+            operations: Vec::new(),
+            step: 0,
+        }
+    }
+
+    pub fn model_name(&self) -> &str {
+        self.model.name()
+    }
+
+    /// 操作を1つ適用する。undoした後なら、その先の履歴は捨てる。
+    /// 実行できない操作ならエラーを返し、何も変えない。
+    pub fn apply(&mut self, op: Operation) -> Result<(), OperationError> {
+        self.validate(&op)?;
+
+        self.history.truncate(self.step + 1);
+        self.operations.truncate(self.step);
+
+        let mut stacks = self.stacks().clone();
+        self.model.move_crates(&mut stacks, &op);
+
+        self.history.push(stacks);
+        self.operations.push(op);
+        self.step += 1;
+        Ok(())
+    }
+
+    /// 順に適用する。エラーになったら、その前の操作までが適用された状態で止まる。
+    pub fn apply_all(
+        &mut self,
+        operations: impl IntoIterator<Item = Operation>,
+    ) -> Result<(), OperationError> {
+        for op in operations {
+            self.apply(op)?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self, op: &Operation) -> Result<(), OperationError> {
+        let stacks = self.stacks();
+        let index = self.step;
+        for stack in [op.from, op.to] {
+            if stack == 0 || stack as usize > stacks.len() {
+                return Err(OperationError::NoSuchStack {
+                    index,
+                    stack,
+                    stacks: stacks.len(),
+                });
+            }
+        }
+
+        let available = stacks[(op.from - 1) as usize].len();
+        if op.times as usize > available {
+            return Err(OperationError::NotEnoughCrates {
+                index,
+                stack: op.from,
+                requested: op.times,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    /// 1つ戻す。戻せなければfalse。
+    pub fn undo(&mut self) -> bool {
+        if self.step == 0 {
+            return false;
+        }
+        self.step -= 1;
+        true
+    }
+
+    /// 1つ進める。進められなければfalse。
+    pub fn redo(&mut self) -> bool {
+        if self.step + 1 >= self.history.len() {
+            return false;
+        }
+        self.step += 1;
+        true
+    }
+
+    /// 今のステップ(適用済みの操作の数)
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// 今のスタック
+    pub fn stacks(&self) -> &Stacks {
+        &self.history[self.step]
+    }
+
+    /// 指定したステップのスタック。redoできる範囲まで見られる。
+    pub fn stacks_at(&self, step: usize) -> Option<&Stacks> {
+        self.history.get(step)
+    }
+
+    /// 適用済みの操作
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations[..self.step]
+    }
+
+    /// 各スタックの一番上
+    pub fn top(&self) -> String {
+        self.stacks()
+            .iter()
+            .filter_map(|s| s.last().map(String::as_str))
+            .collect()
+    }
+
+    /// 入力と同じ形式の図にする
+    pub fn render(&self) -> String {
+        render_stacks(self.stacks())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::operation::read_operations;
+    use crate::stack::read_stacks;
+
+    fn sample(model: impl Model + 'static) -> (Crane, Vec<Operation>) {
+        let mut r = include_str!("../data/sample.txt").as_bytes();
+        let stacks = read_stacks(&mut r).unwrap();
+        let operations = read_operations(r);
+        (Crane::new(model, stacks), operations)
+    }
+
+    fn stacks(stacks: &[&[&str]]) -> Stacks {
+        stacks
+            .iter()
+            .map(|stack| stack.iter().map(|c| c.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_move_by_9000() {
+        let mut stacks = stacks(&[&["A"], &["B"], &["C", "D"]]);
+
+        CrateMover9000.move_crates(
+            &mut stacks,
+            &Operation {
+                times: 2,
+                from: 3,
+                to: 1,
+            },
+        );
+
+        assert_eq!(stacks, vec![vec!["A", "D", "C"], vec!["B"], vec![]]);
+    }
+
+    #[test]
+    fn test_move_by_9001() {
+        let mut stacks = stacks(&[&["A"], &["B"], &["C", "D"]]);
+
+        CrateMover9001.move_crates(
+            &mut stacks,
+            &Operation {
+                times: 2,
+                from: 3,
+                to: 1,
+            },
+        );
+
+        assert_eq!(stacks, vec![vec!["A", "C", "D"], vec!["B"], vec![]]);
+    }
+
+    #[test]
+    fn test_crane() {
+        let (mut crane, operations) = sample(CrateMover9000);
+        assert_eq!(crane.model_name(), "CrateMover 9000");
+        crane.apply_all(operations).unwrap();
+        assert_eq!(crane.top(), "CMZ");
+        assert_eq!(crane.step(), 4);
+
+        let (mut crane, operations) = sample(CrateMover9001);
+        crane.apply_all(operations).unwrap();
+        assert_eq!(crane.top(), "MCD");
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let (mut crane, operations) = sample(CrateMover9000);
+        crane.apply_all(operations).unwrap();
+
+        assert!(crane.undo());
+        assert!(crane.undo());
+        assert_eq!(crane.step(), 2);
+        assert_eq!(crane.operations().len(), 2);
+        assert_eq!(
+            crane.stacks(),
+            &stacks(&[&[], &["M", "C"], &["P", "D", "N", "Z"]])
+        );
+
+        assert!(crane.redo());
+        assert_eq!(
+            crane.stacks(),
+            &stacks(&[&["C", "M"], &[], &["P", "D", "N", "Z"]])
+        );
+
+        // undoした後に新しい操作をすると、先の履歴は消える
+        crane
+            .apply(Operation {
+                times: 1,
+                from: 3,
+                to: 2,
+            })
+            .unwrap();
+        assert!(!crane.redo());
+        assert_eq!(crane.top(), "MZN");
+
+        while crane.undo() {}
+        assert_eq!(crane.step(), 0);
+        assert_eq!(crane.top(), "NDP");
+        assert_eq!(crane.stacks_at(0), Some(crane.stacks()));
+        assert_eq!(crane.stacks_at(4).map(|s| s.len()), Some(3));
+        assert_eq!(crane.stacks_at(5), None);
+    }
+
+    #[test]
+    fn test_invalid_operation() {
+        let (mut crane, operations) = sample(CrateMover9000);
+        crane.apply_all(operations).unwrap();
+
+        let op = |times, from, to| Operation { times, from, to };
+        assert_eq!(
+            crane.apply(op(1, 0, 1)),
+            Err(OperationError::NoSuchStack {
+                index: 4,
+                stack: 0,
+                stacks: 3
+            })
+        );
+        assert_eq!(
+            crane.apply(op(1, 1, 4)),
+            Err(OperationError::NoSuchStack {
+                index: 4,
+                stack: 4,
+                stacks: 3
+            })
+        );
+        assert_eq!(
+            crane.apply(op(2, 2, 1)),
+            Err(OperationError::NotEnoughCrates {
+                index: 4,
+                stack: 2,
+                requested: 2,
+                available: 1
+            })
+        );
+        // エラーの操作は適用されない
+        assert_eq!(crane.step(), 4);
+        assert_eq!(crane.top(), "CMZ");
+
+        // 途中でエラーになると、そこで止まる
+        let (mut crane, _) = sample(CrateMover9001);
+        let result = crane.apply_all([op(1, 2, 1), op(5, 2, 3), op(1, 1, 2)]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "operation 1: stack 2 has 2 crates but 5 requested"
+        );
+        assert_eq!(crane.step(), 1);
+    }
+
+    #[test]
+    fn test_render() {
+        let (mut crane, operations) = sample(CrateMover9000);
+        assert_eq!(
+            crane.render(),
+            indoc! {"
+                    [D]
+                [N] [C]
+                [Z] [M] [P]
+                 1   2   3
+            "}
+        );
+
+        crane.apply_all(operations).unwrap();
+        assert_eq!(
+            crane.render(),
+            indoc! {"
+                        [Z]
+                        [N]
+                        [D]
+                [C] [M] [P]
+                 1   2   3
+            "}
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::error::Error;
+use std::fmt;
+
+/// スタックの図の誤り。行と桁は1始まり。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrawingError {
+    /// スタック番号の行がない
+    MissingNumbers,
+    /// スタック番号が 1..n の順になっていない
+    Numbering {
+        column: usize,
+        expected: u32,
+        found: String,
+    },
+    /// クレートの外に文字がある
+    UnexpectedChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    /// `]` で閉じていない
+    UnclosedCrate { line: usize, column: usize },
+    /// `[]` のように中身がない
+    EmptyLabel { line: usize, column: usize },
+    /// どのスタック番号の下にもない、または複数の番号にかかっている
+    Misaligned { line: usize, column: usize },
+    /// 同じ行の同じスタックにクレートが2つある
+    DuplicateCrate { line: usize, stack: u32 },
+    /// 下が空いているのにクレートがある
+    Gap { line: usize, stack: u32 },
+}
+
+impl fmt::Display for DrawingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawingError::MissingNumbers => write!(f, "missing stack numbers"),
+            DrawingError::Numbering {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "column {}: expected stack number {} but found {:?}",
+                column, expected, found
+            ),
+            DrawingError::UnexpectedChar {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {}, column {}: unexpected {:?}",
+                line, column, found
+            ),
+            DrawingError::UnclosedCrate { line, column } => {
+                write!(f, "line {}, column {}: unclosed crate", line, column)
+            }
+            DrawingError::EmptyLabel { line, column } => {
+                write!(f, "line {}, column {}: empty crate label", line, column)
+            }
+            DrawingError::Misaligned { line, column } => write!(
+                f,
+                "line {}, column {}: crate is not aligned with a stack number",
+                line, column
+            ),
+            DrawingError::DuplicateCrate { line, stack } => {
+                write!(f, "line {}: two crates on stack {}", line, stack)
+            }
+            DrawingError::Gap { line, stack } => {
+                write!(
+                    f,
+                    "line {}: crate on stack {} has a gap under it",
+                    line, stack
+                )
+            }
+        }
+    }
+}
+
+impl Error for DrawingError {}
+
+/// 今のスタックに対して実行できない操作。indexは操作の番号(0始まり)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperationError {
+    /// 0番や、存在しないスタック
+    NoSuchStack {
+        index: usize,
+        stack: u32,
+        stacks: usize,
+    },
+    /// スタックにあるより多く運ぼうとした
+    NotEnoughCrates {
+        index: usize,
+        stack: u32,
+        requested: u32,
+        available: usize,
+    },
+}
+
+impl fmt::Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperationError::NoSuchStack {
+                index,
+                stack,
+                stacks,
+            } => write!(
+                f,
+                "operation {}: no stack {} (stacks are 1..={})",
+                index, stack, stacks
+            ),
+            OperationError::NotEnoughCrates {
+                index,
+                stack,
+                requested,
+                available,
+            } => write!(
+                f,
+                "operation {}: stack {} has {} crates but {} requested",
+                index, stack, available, requested
+            ),
+        }
+    }
+}
+
+impl Error for OperationError {}
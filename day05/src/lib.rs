@@ -1,25 +1,27 @@
 use std::io::BufRead;
 
-use crate::operation::read_operations;
+pub use crate::crane::{Crane, CrateMover9000, CrateMover9001, Model};
+pub use crate::error::{DrawingError, OperationError};
+pub use crate::operation::read_operations;
 pub use crate::operation::Operation;
-use crate::stack::read_stacks;
+pub use crate::planner::{plan, render_operations, Target};
+pub use crate::stack::{read_stacks, render_stacks, Stacks};
 
+mod crane;
+mod error;
 mod operation;
+mod planner;
 mod stack;
 
-pub fn simulate(
-    mut r: impl BufRead,
-    mover: fn(stacks: &mut [Vec<char>], op: &Operation),
-) -> String {
-    let mut stacks = read_stacks(&mut r);
+pub fn simulate(mut r: impl BufRead, model: impl Model + 'static) -> anyhow::Result<String> {
+    let stacks = read_stacks(&mut r)?;
     let operations = read_operations(r);
 
-    for op in operations {
-        mover(&mut stacks, &op);
-    }
+    let mut crane = Crane::new(model, stacks);
+    crane.apply_all(operations)?;
 
     // read top of stacks
-    stacks.iter().filter_map(|s| s.last()).collect()
+    Ok(crane.top())
 }
 
 #[cfg(test)]
@@ -29,13 +31,13 @@ mod test {
     #[test]
     fn test_read() {
         let mut r = include_str!("../data/sample.txt").as_bytes();
-        let stacks = read_stacks(&mut r);
+        let stacks = read_stacks(&mut r).unwrap();
         let operations = read_operations(r);
 
         assert_eq!(stacks.len(), 3);
-        assert_eq!(stacks[0], vec!['Z', 'N']);
-        assert_eq!(stacks[1], vec!['M', 'C', 'D']);
-        assert_eq!(stacks[2], vec!['P']);
+        assert_eq!(stacks[0], vec!["Z", "N"]);
+        assert_eq!(stacks[1], vec!["M", "C", "D"]);
+        assert_eq!(stacks[2], vec!["P"]);
 
         assert_eq!(operations.len(), 4);
         assert_eq!(
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::io::BufRead;
 
 use nom::bytes::complete::tag;
@@ -6,13 +7,19 @@ use nom::character::complete::space1;
 use nom::sequence::preceded;
 use nom::IResult;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Operation {
     pub times: u32,
     pub from: u32,
     pub to: u32,
 }
 
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.times, self.from, self.to)
+    }
+}
+
 pub fn read_operations(r: impl BufRead) -> Vec<Operation> {
     r.lines()
         .flatten()
@@ -58,5 +65,6 @@ mod test {
                 to: 3
             }
         );
+        assert_eq!(op.to_string(), "move 12 from 9 to 3");
     }
 }
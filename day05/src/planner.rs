@@ -0,0 +1,385 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{bail, Result};
+
+use crate::crane::{Crane, Model};
+use crate::operation::{read_operations, Operation};
+use crate::stack::Stacks;
+
+/// 幅優先探索で調べる状態の数の上限。超えたら組み立てで計画を作る。
+const SEARCH_LIMIT: usize = 10_000;
+
+/// 並べ替えの目標
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// 最終的なスタックの図
+    Layout(Stacks),
+    /// 各スタックの一番上。すべてのスタックが空でないこと。
+    Top(Vec<String>),
+}
+
+impl Target {
+    /// "CMZ" のような一番上の文字列。1文字を1つのクレートとする。
+    pub fn top(top: &str) -> Self {
+        Target::Top(top.chars().map(|c| c.to_string()).collect())
+    }
+
+    fn is_reached(&self, stacks: &Stacks) -> bool {
+        match self {
+            Target::Layout(layout) => stacks == layout,
+            Target::Top(top) => {
+                stacks.len() == top.len()
+                    && stacks
+                        .iter()
+                        .zip(top)
+                        .all(|(stack, label)| stack.last() == Some(label))
+            }
+        }
+    }
+}
+
+/// スタックを目標の状態にする操作の列を作る。
+///
+/// まず状態数の上限まで幅優先探索をして、最短の操作列を探す。
+/// 見つからなければ、スタックを1つずつ下から組み立てる(3つ以上のスタックが必要)。
+/// 作った操作列は `move` の形式で書き出し、読み直してクレーンで確かめてから返す。
+pub fn plan(
+    model: impl Model + 'static,
+    stacks: &Stacks,
+    target: &Target,
+) -> Result<Vec<Operation>> {
+    let layout = match target {
+        Target::Layout(layout) => {
+            check_layout(stacks, layout)?;
+            layout.clone()
+        }
+        Target::Top(top) => top_layout(stacks, top)?,
+    };
+
+    let operations = match search(&model, stacks, target) {
+        Some(operations) => operations,
+        None if stacks.len() >= 3 => build(&model, stacks, &layout),
+        None => bail!("no plan found within {} states", SEARCH_LIMIT),
+    };
+
+    verify(model, stacks, target, &operations)?;
+    Ok(operations)
+}
+
+/// 操作を `move N from A to B` の形式で1行ずつ書き出す
+pub fn render_operations(operations: &[Operation]) -> String {
+    operations.iter().map(|op| format!("{}\n", op)).collect()
+}
+
+fn verify(
+    model: impl Model + 'static,
+    stacks: &Stacks,
+    target: &Target,
+    operations: &[Operation],
+) -> Result<()> {
+    let text = render_operations(operations);
+    let operations = read_operations(text.as_bytes());
+
+    let mut crane = Crane::new(model, stacks.clone());
+    crane.apply_all(operations)?;
+    if !target.is_reached(crane.stacks()) {
+        bail!("plan does not reach the target:\n{}", crane.render());
+    }
+    Ok(())
+}
+
+fn count_crates(stacks: &Stacks) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for label in stacks.iter().flatten() {
+        *counts.entry(label.as_str()).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn check_layout(stacks: &Stacks, layout: &Stacks) -> Result<()> {
+    if stacks.len() != layout.len() {
+        bail!(
+            "expected {} stacks but target has {}",
+            stacks.len(),
+            layout.len()
+        );
+    }
+    if count_crates(stacks) != count_crates(layout) {
+        bail!("target does not have the same crates");
+    }
+    Ok(())
+}
+
+/// 一番上だけが決まっている目標を、なるべく今のスタックに近い図にする。
+/// すでに一番上が合っているスタックはそのままにし、
+/// 他のスタックには、一番浅いところにある目的のクレートを持ってくる。
+fn top_layout(stacks: &Stacks, top: &[String]) -> Result<Stacks> {
+    if stacks.len() != top.len() {
+        bail!(
+            "expected {} crates on top but got {}",
+            stacks.len(),
+            top.len()
+        );
+    }
+
+    let mut layout = stacks.clone();
+    let fixed: Vec<bool> = stacks
+        .iter()
+        .zip(top)
+        .map(|(stack, label)| stack.last() == Some(label))
+        .collect();
+
+    for (i, label) in top.iter().enumerate() {
+        if fixed[i] {
+            continue;
+        }
+
+        // 一番上が合っているスタックの一番上は取らない
+        let found = layout
+            .iter()
+            .enumerate()
+            .filter_map(|(s, stack)| {
+                let len = stack.len() - usize::from(fixed[s]);
+                let depth = stack[..len].iter().rev().position(|l| l == label)?;
+                Some((depth, s, len - 1 - depth))
+            })
+            .min();
+        let Some((_, s, level)) = found else {
+            bail!("not enough crates {:?} for the target", label);
+        };
+        layout[s].remove(level);
+    }
+
+    for (i, label) in top.iter().enumerate() {
+        if !fixed[i] {
+            layout[i].push(label.clone());
+        }
+    }
+
+    Ok(layout)
+}
+
+/// 幅優先探索で最短の操作列を探す。上限を超えたらNone。
+fn search(model: &dyn Model, stacks: &Stacks, target: &Target) -> Option<Vec<Operation>> {
+    if target.is_reached(stacks) {
+        return Some(Vec::new());
+    }
+
+    // 状態 -> (前の状態, 操作)
+    let mut visited: HashMap<Stacks, Option<(Stacks, Operation)>> = HashMap::new();
+    visited.insert(stacks.clone(), None);
+    let mut queue = VecDeque::from([stacks.clone()]);
+
+    while let Some(current) = queue.pop_front() {
+        for op in all_operations(&current) {
+            let mut next = current.clone();
+            model.move_crates(&mut next, &op);
+            if visited.contains_key(&next) {
+                continue;
+            }
+
+            let reached = target.is_reached(&next);
+            visited.insert(next.clone(), Some((current.clone(), op)));
+            if reached {
+                return Some(backtrack(&visited, next));
+            }
+            if visited.len() > SEARCH_LIMIT {
+                return None;
+            }
+            queue.push_back(next);
+        }
+    }
+
+    None
+}
+
+fn all_operations(stacks: &Stacks) -> Vec<Operation> {
+    let mut operations = Vec::new();
+    for (from, stack) in stacks.iter().enumerate() {
+        for to in (0..stacks.len()).filter(|&to| to != from) {
+            for times in 1..=stack.len() {
+                operations.push(Operation {
+                    times: times as u32,
+                    from: from as u32 + 1,
+                    to: to as u32 + 1,
+                });
+            }
+        }
+    }
+    operations
+}
+
+fn backtrack(
+    visited: &HashMap<Stacks, Option<(Stacks, Operation)>>,
+    mut current: Stacks,
+) -> Vec<Operation> {
+    let mut operations = Vec::new();
+    while let Some(Some((prev, op))) = visited.get(&current) {
+        operations.push(op.clone());
+        current = prev.clone();
+    }
+    operations.reverse();
+    operations
+}
+
+/// スタックを1つずつ、下から目標どおりに組み立てる。
+///
+/// 各スタックの、目標と同じ下の部分は固定し、その上のクレートは自由に動かす。
+/// 目的のクレートの上にあるものは、まとめて別のスタックに移してから1つ運ぶ。
+/// どけたクレートの順番は機種によって変わるので、機種に合わせて動かしながら作る。
+fn build(model: &dyn Model, stacks: &Stacks, layout: &Stacks) -> Vec<Operation> {
+    let n = stacks.len();
+    let mut current = stacks.clone();
+    let mut operations = Vec::new();
+
+    let mut fixed: Vec<usize> = current
+        .iter()
+        .zip(layout)
+        .map(|(a, b)| a.iter().zip(b).take_while(|(x, y)| x == y).count())
+        .collect();
+
+    let mut push = |current: &mut Stacks, times: usize, from: usize, to: usize| {
+        let op = Operation {
+            times: times as u32,
+            from: from as u32 + 1,
+            to: to as u32 + 1,
+        };
+        model.move_crates(current, &op);
+        operations.push(op);
+    };
+
+    for s in 0..n {
+        while fixed[s] < layout[s].len() {
+            // 組み立てるスタックの、固定した部分より上をどける
+            let loose = current[s].len() - fixed[s];
+            if loose > 0 {
+                let to = (0..n).find(|&t| t != s).unwrap();
+                push(&mut current, loose, s, to);
+            }
+
+            // 一番浅いところにある目的のクレート
+            let label = &layout[s][fixed[s]];
+            let (_, from, level) = (0..n)
+                .filter(|&t| t != s)
+                .filter_map(|t| {
+                    let free = &current[t][fixed[t]..];
+                    let depth = free.iter().rev().position(|l| l == label)?;
+                    Some((depth, t, current[t].len() - 1 - depth))
+                })
+                .min()
+                .expect("crates are checked before building");
+
+            let above = current[from].len() - 1 - level;
+            if above > 0 {
+                let to = (0..n).find(|&t| t != s && t != from).unwrap();
+                push(&mut current, above, from, to);
+            }
+            push(&mut current, 1, from, s);
+            fixed[s] += 1;
+        }
+    }
+
+    // 固定した部分の合計がクレートの数になるので、余りはない
+    debug_assert_eq!(&current, layout);
+    operations
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::stack::read_stacks;
+
+    fn sample_stacks() -> Stacks {
+        read_stacks(&mut include_str!("../data/sample.txt").as_bytes()).unwrap()
+    }
+
+    fn stacks(text: &str) -> Stacks {
+        read_stacks(&mut text.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_plan_top() {
+        let operations = plan(CrateMover9000, &sample_stacks(), &Target::top("CMZ")).unwrap();
+
+        // 問題の操作は4つなので、それより短い
+        assert!(operations.len() <= 4);
+
+        let mut crane = Crane::new(CrateMover9000, sample_stacks());
+        crane
+            .apply_all(read_operations(render_operations(&operations).as_bytes()))
+            .unwrap();
+        assert_eq!(crane.top(), "CMZ");
+
+        assert_eq!(
+            plan(CrateMover9001, &sample_stacks(), &Target::top("NDP")).unwrap(),
+            vec![]
+        );
+        assert!(plan(CrateMover9001, &sample_stacks(), &Target::top("XYZ")).is_err());
+        assert!(plan(CrateMover9001, &sample_stacks(), &Target::top("NN")).is_err());
+    }
+
+    #[test]
+    fn test_plan_layout() {
+        let target = stacks(indoc! {"
+            [D] [N] [Z]
+            [C] [M] [P]
+             1   2   3
+        "});
+
+        for model in [&CrateMover9000 as &dyn Model, &CrateMover9001] {
+            let operations = search(model, &sample_stacks(), &Target::Layout(target.clone()));
+            assert!(operations.is_some());
+        }
+
+        let operations = plan(
+            CrateMover9001,
+            &sample_stacks(),
+            &Target::Layout(target.clone()),
+        )
+        .unwrap();
+        assert_eq!(
+            render_operations(&operations),
+            indoc! {"
+                move 2 from 1 to 3
+                move 2 from 2 to 1
+                move 1 from 3 to 2
+            "}
+        );
+
+        // クレートが違う
+        let other = stacks("[A] [N] [Z]\n[C] [M] [P]\n 1   2   3\n");
+        assert!(plan(CrateMover9000, &sample_stacks(), &Target::Layout(other)).is_err());
+    }
+
+    #[test]
+    fn test_build() {
+        // 幅優先探索では見つからない大きさ
+        let labels: Vec<String> = (0..45).map(|i| format!("C{}", i)).collect();
+        let start: Stacks = labels.chunks(5).map(|c| c.to_vec()).collect();
+        let layout: Stacks = labels
+            .chunks(5)
+            .rev()
+            .map(|c| c.iter().rev().cloned().collect())
+            .collect();
+
+        for model in [&CrateMover9000 as &dyn Model, &CrateMover9001] {
+            let operations = build(model, &start, &layout);
+            let mut current = start.clone();
+            for op in &operations {
+                model.move_crates(&mut current, op);
+            }
+            assert_eq!(current, layout);
+        }
+
+        let operations = plan(CrateMover9000, &start, &Target::Layout(layout)).unwrap();
+        assert!(!operations.is_empty());
+
+        // 一番上だけ
+        let top: Vec<String> = (0..9).map(|i| format!("C{}", i * 5)).collect();
+        let operations = plan(CrateMover9001, &start, &Target::Top(top)).unwrap();
+        assert!(!operations.is_empty());
+    }
+}
@@ -1,14 +1,21 @@
 use std::io::BufRead;
+use std::ops::Range;
 
 use itertools::{FoldWhile, Itertools};
 use FoldWhile::{Continue, Done};
 
-pub fn read_stacks(r: &mut impl BufRead) -> Vec<Vec<char>> {
+use crate::error::DrawingError;
+
+/// 番号順のクレートスタック。各スタックは下から順に並ぶ。
+/// クレートのラベルは複数文字でもよい。
+pub type Stacks = Vec<Vec<String>>;
+
+pub fn read_stacks(r: &mut impl BufRead) -> Result<Stacks, DrawingError> {
     // 空行まで読み込み
     let header = r
         .by_ref()
         .lines()
-        .flatten()
+        .map_while(Result::ok)
         .fold_while(String::new(), |mut acc, line| {
             if line.is_empty() {
                 return Done(acc);
@@ -24,35 +31,192 @@ pub fn read_stacks(r: &mut impl BufRead) -> Vec<Vec<char>> {
     parse_crate_stacks(&header)
 }
 
-/// 数字がある位置をVecで返す。
-fn parse_stack_indices(line: &str) -> Vec<usize> {
-    line.char_indices()
-        .filter(|(_, c)| c.is_ascii_digit())
-        .map(|(i, _)| i)
+/// 空白で区切られた単語と、その桁の範囲(文字単位)を返す。
+fn words(line: &str) -> Vec<(Range<usize>, String)> {
+    let mut words = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (i, c) in line.chars().chain([' ']).enumerate() {
+        match (c.is_whitespace(), current.as_mut()) {
+            (true, Some(_)) => {
+                let (start, word) = current.take().unwrap();
+                words.push((start..i, word));
+            }
+            (false, Some((_, word))) => word.push(c),
+            (false, None) => current = Some((i, c.to_string())),
+            (true, None) => {}
+        }
+    }
+    words
+}
+
+/// スタック番号の桁の範囲を返す。番号は1から順になっていること。
+fn parse_stack_numbers(line: &str) -> Result<Vec<Range<usize>>, DrawingError> {
+    words(line)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (range, word))| {
+            let expected = i as u32 + 1;
+            if word.parse() != Ok(expected) {
+                return Err(DrawingError::Numbering {
+                    column: range.start + 1,
+                    expected,
+                    found: word,
+                });
+            }
+            Ok(range)
+        })
         .collect()
 }
 
+/// 1行のクレートを、括弧を含めた桁の範囲とラベルで返す。
+fn parse_crates(line: &str, line_no: usize) -> Result<Vec<(Range<usize>, String)>, DrawingError> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut crates = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            ' ' => i += 1,
+            '[' => {
+                let Some(close) = chars[i..].iter().position(|&c| c == ']') else {
+                    return Err(DrawingError::UnclosedCrate {
+                        line: line_no,
+                        column: i + 1,
+                    });
+                };
+                let label: String = chars[i + 1..i + close].iter().collect();
+                if label.trim().is_empty() {
+                    return Err(DrawingError::EmptyLabel {
+                        line: line_no,
+                        column: i + 1,
+                    });
+                }
+                crates.push((i..i + close + 1, label));
+                i += close + 1;
+            }
+            c => {
+                return Err(DrawingError::UnexpectedChar {
+                    line: line_no,
+                    column: i + 1,
+                    found: c,
+                })
+            }
+        }
+    }
+    Ok(crates)
+}
+
 /// 番号毎に、クレートスタックを取得
-fn parse_crate_stacks(text: &str) -> Vec<Vec<char>> {
-    // 扱いやすいように上下反転
-    let lines: Vec<&str> = text.trim_end().lines().rev().collect();
-    // 番号が書かれている行から、各スタックのオフセットを取得
-    let indices = parse_stack_indices(lines[0]);
+fn parse_crate_stacks(text: &str) -> Result<Stacks, DrawingError> {
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    // 最後の行がスタック番号
+    let Some((numbers, rows)) = lines.split_last() else {
+        return Err(DrawingError::MissingNumbers);
+    };
+    let numbers = parse_stack_numbers(numbers)?;
+    if numbers.is_empty() {
+        return Err(DrawingError::MissingNumbers);
+    }
 
-    indices
-        .into_iter()
-        .map(|index| {
-            lines
+    let mut stacks: Stacks = vec![Vec::new(); numbers.len()];
+    // 下の行で空いていたスタックには、もう積めない
+    let mut open = vec![true; numbers.len()];
+
+    // 下の行から積んでいく
+    for (i, line) in rows.iter().enumerate().rev() {
+        let line_no = i + 1;
+        let mut used = vec![false; numbers.len()];
+
+        for (range, label) in parse_crates(line, line_no)? {
+            // 括弧を含めた範囲が、ちょうど1つの番号にかかっていること
+            let overlapping: Vec<usize> = numbers
                 .iter()
-                .skip(1) // スタック番号の行はスキップ
-                .map_while(|line| match line.chars().nth(index) {
-                    Some(' ') | None => None,
-                    Some('[') | Some(']') => unreachable!("illegal index"), // ここに来るということは、インデックスがずれている。
-                    Some(c) => Some(c),
-                })
-                .collect()
+                .enumerate()
+                .filter(|(_, n)| n.start < range.end && range.start < n.end)
+                .map(|(stack, _)| stack)
+                .collect();
+            let [stack] = overlapping[..] else {
+                return Err(DrawingError::Misaligned {
+                    line: line_no,
+                    column: range.start + 1,
+                });
+            };
+
+            if used[stack] {
+                return Err(DrawingError::DuplicateCrate {
+                    line: line_no,
+                    stack: stack as u32 + 1,
+                });
+            }
+            if !open[stack] {
+                return Err(DrawingError::Gap {
+                    line: line_no,
+                    stack: stack as u32 + 1,
+                });
+            }
+
+            used[stack] = true;
+            stacks[stack].push(label);
+        }
+
+        for (open, used) in open.iter_mut().zip(used) {
+            *open &= used;
+        }
+    }
+
+    Ok(stacks)
+}
+
+/// 入力と同じ形式の図にする。
+/// 各スタックの幅は、一番長いラベルと番号に合わせる。
+pub fn render_stacks(stacks: &Stacks) -> String {
+    let height = stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = stacks
+        .iter()
+        .enumerate()
+        .map(|(i, stack)| {
+            let label = stack.iter().map(|c| c.chars().count()).max().unwrap_or(1);
+            (label + 2).max((i + 1).to_string().len())
         })
-        .collect()
+        .collect();
+
+    let render_line = |cells: Vec<String>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| center(cell, width))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines: Vec<String> = (0..height)
+        .rev()
+        .map(|level| {
+            render_line(
+                stacks
+                    .iter()
+                    .map(|stack| match stack.get(level) {
+                        Some(c) => format!("[{}]", c),
+                        None => String::new(),
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+
+    lines.push(render_line(
+        (1..=stacks.len()).map(|n| n.to_string()).collect(),
+    ));
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn center(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    let left = width.saturating_sub(len) / 2;
+    let right = width.saturating_sub(len + left);
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
 }
 
 #[cfg(test)]
@@ -62,13 +226,37 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_parse_stack_indices() {
-        assert_eq!(parse_stack_indices(" 1   2   3 "), vec![1, 5, 9]);
+    fn test_parse_stack_numbers() {
+        assert_eq!(
+            parse_stack_numbers(" 1   2   3 ").unwrap(),
+            vec![1..2, 5..6, 9..10]
+        );
 
         assert_eq!(
-            parse_stack_indices(" 1   2   3   4   5   6   7   8   9 "),
+            parse_stack_numbers(" 1   2   3   4   5   6   7   8   9 ")
+                .unwrap()
+                .iter()
+                .map(|r| r.start)
+                .collect::<Vec<_>>(),
             vec![1, 5, 9, 13, 17, 21, 25, 29, 33]
         );
+
+        // 2桁の番号
+        assert_eq!(
+            parse_stack_numbers(" 1   2   3   4   5   6   7   8   9  10  11")
+                .unwrap()
+                .last(),
+            Some(&(40..42))
+        );
+
+        assert_eq!(
+            parse_stack_numbers(" 1   3"),
+            Err(DrawingError::Numbering {
+                column: 6,
+                expected: 2,
+                found: "3".to_string()
+            })
+        );
     }
 
     #[test]
@@ -78,11 +266,76 @@ mod test {
             [N] [C]
             [Z] [M] [P]
              1   2   3
-        "#});
+        "#})
+        .unwrap();
 
         assert_eq!(stacks.len(), 3);
-        assert_eq!(stacks[0], vec!['Z', 'N']);
-        assert_eq!(stacks[1], vec!['M', 'C', 'D']);
-        assert_eq!(stacks[2], vec!['P']);
+        assert_eq!(stacks[0], vec!["Z", "N"]);
+        assert_eq!(stacks[1], vec!["M", "C", "D"]);
+        assert_eq!(stacks[2], vec!["P"]);
+    }
+
+    #[test]
+    fn test_multi_char_labels() {
+        let stacks: Stacks = vec![
+            vec!["AB".to_string(), "C".to_string()],
+            vec![],
+            vec!["XYZ".to_string()],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec!["J".to_string(), "K".to_string()],
+        ];
+        let drawing = render_stacks(&stacks);
+        assert_eq!(
+            drawing,
+            indoc! {"
+                [C]                                    [K]
+                [AB]     [XYZ]                         [J]
+                 1    2    3    4   5   6   7   8   9  10
+            "}
+        );
+        assert_eq!(parse_crate_stacks(&drawing).unwrap(), stacks);
+    }
+
+    #[test]
+    fn test_drawing_errors() {
+        let error = |text: &str| parse_crate_stacks(text).unwrap_err();
+
+        // 番号の行がない
+        assert_eq!(error(""), DrawingError::MissingNumbers);
+        // 番号の間に1桁ずれている
+        assert_eq!(
+            error("  [A]\n 1   2\n"),
+            DrawingError::Misaligned { line: 1, column: 3 }
+        );
+        // 下が空いている
+        assert_eq!(
+            error("[A]\n    [B]\n 1   2\n"),
+            DrawingError::Gap { line: 1, stack: 1 }
+        );
+        assert_eq!(
+            error("[A] x\n 1   2\n"),
+            DrawingError::UnexpectedChar {
+                line: 1,
+                column: 5,
+                found: 'x'
+            }
+        );
+        assert_eq!(
+            error("[A\n 1\n"),
+            DrawingError::UnclosedCrate { line: 1, column: 1 }
+        );
+        assert_eq!(
+            error("[]\n 1\n"),
+            DrawingError::EmptyLabel { line: 1, column: 1 }
+        );
+        assert_eq!(
+            error("[A][B]\n 1\n"),
+            DrawingError::Misaligned { line: 1, column: 4 }
+        );
     }
 }
@@ -1,18 +1,21 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+use anyhow::anyhow;
 use day06::find_marker;
+use tools::input::input_path;
 
-fn find_packet_marker(text: &str) -> usize {
+fn find_packet_marker(text: &str) -> Option<usize> {
     // パケットマーカーは4文字
-    find_marker::<4>(text)
+    find_marker(text, 4)
 }
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day06/data/input.txt")?);
+    let path = input_path("day06");
+    let r = BufReader::new(File::open(path)?);
     let text = r.lines().next().unwrap()?;
 
-    let ret = find_packet_marker(&text);
+    let ret = find_packet_marker(&text).ok_or_else(|| anyhow!("marker not found"))?;
     println!("answer: {}", ret);
 
     Ok(())
@@ -24,10 +27,19 @@ mod test {
 
     #[test]
     fn test_find_packet_marker() {
-        assert_eq!(find_packet_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), 7);
-        assert_eq!(find_packet_marker("bvwbjplbgvbhsrlpgdmjqwftvncz"), 5);
-        assert_eq!(find_packet_marker("nppdvjthqldpwncqszvftbrmjlhg"), 6);
-        assert_eq!(find_packet_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 10);
-        assert_eq!(find_packet_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 11);
+        assert_eq!(
+            find_packet_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb"),
+            Some(7)
+        );
+        assert_eq!(find_packet_marker("bvwbjplbgvbhsrlpgdmjqwftvncz"), Some(5));
+        assert_eq!(find_packet_marker("nppdvjthqldpwncqszvftbrmjlhg"), Some(6));
+        assert_eq!(
+            find_packet_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"),
+            Some(10)
+        );
+        assert_eq!(
+            find_packet_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"),
+            Some(11)
+        );
     }
 }
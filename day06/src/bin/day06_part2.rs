@@ -1,18 +1,21 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+use anyhow::anyhow;
 use day06::find_marker;
+use tools::input::input_path;
 
-pub fn find_message_marker(text: &str) -> usize {
+pub fn find_message_marker(text: &str) -> Option<usize> {
     // メッセージマーカーは14文字
-    find_marker::<14>(text)
+    find_marker(text, 14)
 }
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day06/data/input.txt")?);
+    let path = input_path("day06");
+    let r = BufReader::new(File::open(path)?);
     let text = r.lines().next().unwrap()?;
 
-    let ret = find_message_marker(&text);
+    let ret = find_message_marker(&text).ok_or_else(|| anyhow!("marker not found"))?;
     println!("answer: {}", ret);
 
     Ok(())
@@ -24,10 +27,25 @@ mod test {
 
     #[test]
     fn test_find_message_marker() {
-        assert_eq!(find_message_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), 19);
-        assert_eq!(find_message_marker("bvwbjplbgvbhsrlpgdmjqwftvncz"), 23);
-        assert_eq!(find_message_marker("nppdvjthqldpwncqszvftbrmjlhg"), 23);
-        assert_eq!(find_message_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 29);
-        assert_eq!(find_message_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 26);
+        assert_eq!(
+            find_message_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb"),
+            Some(19)
+        );
+        assert_eq!(
+            find_message_marker("bvwbjplbgvbhsrlpgdmjqwftvncz"),
+            Some(23)
+        );
+        assert_eq!(
+            find_message_marker("nppdvjthqldpwncqszvftbrmjlhg"),
+            Some(23)
+        );
+        assert_eq!(
+            find_message_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"),
+            Some(29)
+        );
+        assert_eq!(
+            find_message_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"),
+            Some(26)
+        );
     }
 }
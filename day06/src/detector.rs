@@ -0,0 +1,198 @@
+use std::io::{self, BufRead};
+
+/// 直近 size 文字の出現数を持ちながら、1文字ずつマーカーを探す。
+/// 1文字あたり O(1) で判定する。
+#[derive(Debug, Clone)]
+pub struct MarkerDetector {
+    size: usize,
+    // 直近 size 文字のリングバッファ
+    window: Vec<u8>,
+    counts: [u32; 256],
+    // 窓の中の文字の種類数
+    distinct: usize,
+    // これまでに読んだ文字数
+    position: usize,
+}
+
+impl MarkerDetector {
+    /// size が0ならマーカーはないので None
+    pub fn new(size: usize) -> Option<Self> {
+        (size > 0).then(|| Self {
+            size,
+            window: vec![0; size],
+            counts: [0; 256],
+            distinct: 0,
+            position: 0,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// これまでに読んだ文字数
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// 1文字読み込む。直近 size 文字がすべて違えば、マーカーの終わり位置を返す。
+    pub fn push(&mut self, byte: u8) -> Option<usize> {
+        let slot = self.position % self.size;
+        if self.position >= self.size {
+            // 窓から出る文字
+            let old = self.window[slot] as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 0 {
+                self.distinct -= 1;
+            }
+        }
+
+        self.window[slot] = byte;
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 1 {
+            self.distinct += 1;
+        }
+        self.position += 1;
+
+        (self.distinct == self.size).then_some(self.position)
+    }
+
+    /// 最初からやり直す
+    pub fn reset(&mut self) {
+        self.counts = [0; 256];
+        self.distinct = 0;
+        self.position = 0;
+    }
+}
+
+/// 読み込みながら、すべてのマーカーの終わり位置を返すイテレータ。
+/// 改行(`\r`, `\n`)は信号に含めず、位置も数えない。
+pub struct Markers<R> {
+    reader: R,
+    // size が0なら None で、何も返さない
+    detector: Option<MarkerDetector>,
+    done: bool,
+}
+
+pub fn markers<R: BufRead>(reader: R, size: usize) -> Markers<R> {
+    let detector = MarkerDetector::new(size);
+    Markers {
+        reader,
+        done: detector.is_none(),
+        detector,
+    }
+}
+
+impl<R: BufRead> Iterator for Markers<R> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            if buf.is_empty() {
+                self.done = true;
+                break;
+            }
+
+            // マーカーが見つかったら、そこまでだけ読んだことにする
+            let mut found = None;
+            let mut used = 0;
+            for &byte in buf {
+                used += 1;
+                if byte == b'\n' || byte == b'\r' {
+                    continue;
+                }
+                let detector = self.detector.as_mut().expect("not done");
+                if let Some(position) = detector.push(byte) {
+                    found = Some(position);
+                    break;
+                }
+            }
+            self.reader.consume(used);
+
+            if found.is_some() {
+                return found.map(Ok);
+            }
+        }
+        None
+    }
+}
+
+/// 最初のマーカーの終わり位置
+pub fn first_marker(reader: impl BufRead, size: usize) -> io::Result<Option<usize>> {
+    markers(reader, size).next().transpose()
+}
+
+/// 文字列の中の最初のマーカーの終わり位置
+pub fn find_marker(text: &str, size: usize) -> Option<usize> {
+    let mut detector = MarkerDetector::new(size)?;
+    text.bytes().find_map(|byte| detector.push(byte))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::BufReader;
+
+    use super::*;
+
+    #[test]
+    fn test_detector() {
+        let mut detector = MarkerDetector::new(4).unwrap();
+        let found: Vec<Option<usize>> = "abcdd".bytes().map(|b| detector.push(b)).collect();
+        assert_eq!(found, vec![None, None, None, Some(4), None]);
+
+        detector.reset();
+        assert_eq!(detector.position(), 0);
+        assert_eq!(detector.push(b'a'), None);
+
+        // 1文字のマーカーはすべての位置
+        let mut detector = MarkerDetector::new(1).unwrap();
+        assert_eq!(detector.push(b'a'), Some(1));
+        assert_eq!(detector.push(b'a'), Some(2));
+
+        // 0文字のマーカーはない
+        assert!(MarkerDetector::new(0).is_none());
+    }
+
+    #[test]
+    fn test_find_marker() {
+        assert_eq!(find_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4), Some(7));
+        assert_eq!(find_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), Some(19));
+        assert_eq!(find_marker("aaaaaaaa", 2), None);
+        assert_eq!(find_marker("abc", 4), None);
+        assert_eq!(find_marker("abc", 0), None);
+    }
+
+    #[test]
+    fn test_markers() {
+        let all: Vec<usize> = markers("abcabb\n".as_bytes(), 3)
+            .map(|m| m.unwrap())
+            .collect();
+        assert_eq!(all, vec![3, 4, 5]);
+
+        // 改行は数えない
+        assert_eq!(first_marker("ab\r\ncd\n".as_bytes(), 4).unwrap(), Some(4));
+        assert_eq!(first_marker("".as_bytes(), 4).unwrap(), None);
+        assert_eq!(markers("abc\n".as_bytes(), 0).count(), 0);
+    }
+
+    #[test]
+    fn test_large_signal() {
+        // 小さなバッファで、バッファの境目をまたいで読む
+        let mut signal = "ab".repeat(1 << 20).into_bytes();
+        signal.extend(b"cdefghijklmnop");
+        let reader = BufReader::with_capacity(7, signal.as_slice());
+
+        let all: Vec<usize> = markers(reader, 14).map(|m| m.unwrap()).collect();
+        // "ab" + 12文字, "b" + 13文字, 最後の14文字
+        let len = signal.len();
+        assert_eq!(all, vec![len - 2, len - 1, len]);
+    }
+}
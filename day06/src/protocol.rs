@@ -0,0 +1,312 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::detector::MarkerDetector;
+
+/// マーカーの種類。マーカーの後に payload 文字のデータが続く。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkerKind {
+    pub name: String,
+    pub size: usize,
+    pub payload: usize,
+}
+
+impl MarkerKind {
+    pub fn new(name: &str, size: usize, payload: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            size,
+            payload,
+        }
+    }
+
+    /// start-of-packet マーカー(4文字)
+    pub fn packet(payload: usize) -> Self {
+        Self::new("packet", 4, payload)
+    }
+
+    /// start-of-message マーカー(14文字)
+    pub fn message(payload: usize) -> Self {
+        Self::new("message", 14, payload)
+    }
+}
+
+/// 取り出したフレーム。offset はマーカーの、payload_offset はデータの始まり位置。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub kind: String,
+    pub offset: usize,
+    pub payload_offset: usize,
+    pub payload: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// データの途中で信号が終わった
+    Truncated {
+        kind: String,
+        offset: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// a-z 以外の文字があった。フレームの中なら kind が入る。
+    Corrupted {
+        kind: Option<String>,
+        offset: usize,
+        byte: u8,
+    },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Truncated {
+                kind,
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} at {}: expected {} bytes of payload but found {}",
+                kind, offset, expected, found
+            ),
+            FrameError::Corrupted {
+                kind: Some(kind),
+                offset,
+                byte,
+            } => write!(f, "{}: corrupted byte {:#04x} at {}", kind, byte, offset),
+            FrameError::Corrupted {
+                kind: None,
+                offset,
+                byte,
+            } => write!(f, "corrupted byte {:#04x} at {}", byte, offset),
+        }
+    }
+}
+
+impl Error for FrameError {}
+
+/// 信号をマーカーで区切ってフレームにする。
+///
+/// マーカーの前の文字は雑音として読み飛ばす。
+/// 短いマーカーは長いマーカーの中にも現れるので、見つけたマーカーを含む
+/// より長いマーカーがないことを確かめてから(長さの差だけ先まで読んでから)決める。
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    kinds: Vec<MarkerKind>,
+    // kinds と同じ順の、まだ何も読んでいない検出器
+    detectors: Vec<MarkerDetector>,
+}
+
+impl Decoder {
+    /// 長さ0のマーカーがあればNone
+    pub fn new(kinds: Vec<MarkerKind>) -> Option<Self> {
+        let detectors = kinds
+            .iter()
+            .map(|kind| MarkerDetector::new(kind.size))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { kinds, detectors })
+    }
+
+    /// start-of-packet と start-of-message
+    pub fn standard(packet: usize, message: usize) -> Self {
+        Self::new(vec![
+            MarkerKind::packet(packet),
+            MarkerKind::message(message),
+        ])
+        .expect("standard markers are not empty")
+    }
+
+    pub fn kinds(&self) -> &[MarkerKind] {
+        &self.kinds
+    }
+
+    /// 末尾の改行は信号に含めない
+    pub fn decode(&self, signal: &[u8]) -> Vec<Result<Frame, FrameError>> {
+        let signal = signal
+            .strip_suffix(b"\n")
+            .map(|s| s.strip_suffix(b"\r").unwrap_or(s))
+            .unwrap_or(signal);
+
+        let mut frames = Vec::new();
+        let mut pos = 0;
+        while pos < signal.len() {
+            let (marker, noise_errors) = self.find_marker(signal, pos);
+            frames.extend(noise_errors.into_iter().map(Err));
+            let Some((kind, start)) = marker else {
+                break;
+            };
+
+            let kind = &self.kinds[kind];
+            let payload_start = start + kind.size;
+            let payload_end = payload_start + kind.payload;
+            if payload_end > signal.len() {
+                frames.push(Err(FrameError::Truncated {
+                    kind: kind.name.clone(),
+                    offset: start,
+                    expected: kind.payload,
+                    found: signal.len() - payload_start,
+                }));
+                break;
+            }
+
+            let payload = &signal[payload_start..payload_end];
+            match payload.iter().position(|b| !b.is_ascii_lowercase()) {
+                Some(i) => frames.push(Err(FrameError::Corrupted {
+                    kind: Some(kind.name.clone()),
+                    offset: payload_start + i,
+                    byte: payload[i],
+                })),
+                None => frames.push(Ok(Frame {
+                    kind: kind.name.clone(),
+                    offset: start,
+                    payload_offset: payload_start,
+                    payload: String::from_utf8(payload.to_vec()).unwrap(),
+                })),
+            }
+            pos = payload_end;
+        }
+
+        frames
+    }
+
+    /// pos から次のマーカーを探し、(種類, 始まり位置) を返す。
+    /// 途中の a-z 以外の文字はエラーとして返し、そこから探し直す。
+    fn find_marker(&self, signal: &[u8], pos: usize) -> (Option<(usize, usize)>, Vec<FrameError>) {
+        let longest = self.kinds.iter().map(|kind| kind.size).max().unwrap_or(0);
+        let mut detectors = self.detectors.clone();
+        let mut errors = Vec::new();
+        // (種類, 始まり位置)
+        let mut candidate: Option<(usize, usize)> = None;
+
+        for (i, &byte) in signal.iter().enumerate().skip(pos) {
+            // これより先では、候補を含む長いマーカーは現れない
+            if candidate.is_some_and(|(_, start)| i >= start + longest) {
+                break;
+            }
+
+            if !byte.is_ascii_lowercase() {
+                if candidate.is_some() {
+                    break;
+                }
+                errors.push(FrameError::Corrupted {
+                    kind: None,
+                    offset: i,
+                    byte,
+                });
+                detectors.iter_mut().for_each(|d| d.reset());
+                continue;
+            }
+
+            for (kind, detector) in detectors.iter_mut().enumerate() {
+                if detector.push(byte).is_none() {
+                    continue;
+                }
+                let size = self.kinds[kind].size;
+                let start = i + 1 - size;
+                // 長いマーカーが候補を含んでいれば置き換える
+                if candidate
+                    .is_none_or(|(c, c_start)| size > self.kinds[c].size && start <= c_start)
+                {
+                    candidate = Some((kind, start));
+                }
+            }
+        }
+
+        (candidate, errors)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frame(
+        kind: &str,
+        offset: usize,
+        payload_offset: usize,
+        payload: &str,
+    ) -> Result<Frame, FrameError> {
+        Ok(Frame {
+            kind: kind.to_string(),
+            offset,
+            payload_offset,
+            payload: payload.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_decode() {
+        let decoder = Decoder::standard(3, 5);
+
+        // 雑音、パケット、雑音、メッセージ
+        // メッセージのマーカーの中の "abcd" はパケットにしない
+        let signal = b"aaaabcdxyzaaaaabcdefghijklmnhello\n";
+        assert_eq!(
+            decoder.decode(signal),
+            vec![
+                frame("packet", 3, 7, "xyz"),
+                frame("message", 14, 28, "hello"),
+            ]
+        );
+
+        let frames = decoder.decode(include_bytes!("../data/sample.txt"));
+        assert_eq!(
+            frames,
+            vec![
+                frame("packet", 3, 7, "gbl"),
+                frame("packet", 10, 14, "dzt"),
+                frame("packet", 17, 21, "qwr"),
+                Err(FrameError::Truncated {
+                    kind: "packet".to_string(),
+                    offset: 24,
+                    expected: 3,
+                    found: 2
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let decoder = Decoder::new(vec![MarkerKind::new("p", 2, 2)]).unwrap();
+
+        assert_eq!(
+            decoder.decode(b"aab1xab"),
+            vec![
+                Err(FrameError::Corrupted {
+                    kind: Some("p".to_string()),
+                    offset: 3,
+                    byte: b'1'
+                }),
+                Err(FrameError::Truncated {
+                    kind: "p".to_string(),
+                    offset: 5,
+                    expected: 2,
+                    found: 0
+                }),
+            ]
+        );
+
+        let errors = decoder.decode(b"a#aabcc");
+        assert_eq!(
+            errors[0],
+            Err(FrameError::Corrupted {
+                kind: None,
+                offset: 1,
+                byte: b'#'
+            })
+        );
+        assert_eq!(errors[1], frame("p", 3, 5, "cc"));
+        assert_eq!(
+            errors[0].clone().unwrap_err().to_string(),
+            "corrupted byte 0x23 at 1"
+        );
+    }
+
+    #[test]
+    fn test_empty_marker() {
+        assert!(Decoder::new(vec![MarkerKind::new("x", 0, 1)]).is_none());
+        assert!(Decoder::new(vec![MarkerKind::packet(1), MarkerKind::new("x", 0, 1)]).is_none());
+    }
+}
@@ -3,9 +3,11 @@ use std::io::{BufRead, BufReader};
 
 use day07::command::CommandParse;
 use day07::directory::IntoDirectories;
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day07/data/input.txt")?);
+    let path = input_path("day07");
+    let r = BufReader::new(File::open(path)?);
 
     let total_size = calc_size(r)?;
     println!("answer: {}", total_size);
@@ -18,7 +20,7 @@ fn calc_size(mut r: impl BufRead) -> anyhow::Result<u64> {
 
     let total_size = r
         .parse_commands()?
-        .into_directories()
+        .into_directories()?
         .iter()
         .filter(|d| d.size <= THRESHOLD)
         .map(|d| d.size)
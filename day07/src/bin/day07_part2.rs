@@ -4,9 +4,11 @@ use std::path::Path;
 
 use day07::command::CommandParse;
 use day07::directory::{Directory, IntoDirectories};
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day07/data/input.txt")?);
+    let path = input_path("day07");
+    let r = BufReader::new(File::open(path)?);
 
     let directory = find_directory_to_delete(r)?;
     println!("dir: {}", directory.path.to_string_lossy());
@@ -18,7 +20,7 @@ fn find_directory_to_delete(mut r: impl BufRead) -> anyhow::Result<Directory> {
     const STORAGE_MAX: u64 = 70000000;
     const STORAGE_NEED_SPACE: u64 = 30000000;
 
-    let directories = r.parse_commands()?.into_directories();
+    let directories = r.parse_commands()?.into_directories()?;
 
     let root_size = directories
         .iter()
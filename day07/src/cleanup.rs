@@ -0,0 +1,867 @@
+use std::cmp::Reverse;
+
+use anyhow::{bail, Result};
+
+use crate::filesystem::{FileSystem, NodeId};
+
+/// 何を最小にするか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    /// 削除するバイト数
+    MinBytes,
+    /// 削除する数。同じ数ならバイト数が少ない方。
+    FewestItems,
+}
+
+/// 削除の計画。deleted はディレクトリかファイルで、互いに含み合わない。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanupPlan {
+    /// 空ける必要のあるバイト数
+    pub need: u64,
+    pub deleted: Vec<NodeId>,
+    pub freed: u64,
+}
+
+impl CleanupPlan {
+    pub fn render(&self, fs: &FileSystem) -> String {
+        let mut lines = format!("need: {}\n", self.need);
+        for &id in &self.deleted {
+            let node = fs.node(id);
+            let kind = if node.is_dir() { "dir" } else { "file" };
+            lines.push_str(&format!(
+                "delete {} ({}, size={})\n",
+                fs.path(id).display(),
+                kind,
+                node.size()
+            ));
+        }
+        lines.push_str(&format!(
+            "freed: {} ({} items)\n",
+            self.freed,
+            self.deleted.len()
+        ));
+        lines
+    }
+}
+
+/// DPに使ってよいメモリ(バイト)
+const MAX_DP_BYTES: u64 = 1 << 30;
+
+/// DPできないときに全部試してよい、削除する組み合わせの数
+const MAX_SEARCH_COMBINATIONS: u64 = 1 << 20;
+
+/// 容量 disk のうち required を空けるために削除するものを選ぶ。
+///
+/// ノードを深さ優先の順に並べ、各位置で「削除する(部分木を飛ばす)」か「残す(中に入る)」
+/// の2通りに進むナップサック。作れるバイト数の集合をビット列で持ち、ディレクトリに入るときだけ
+/// その時点の集合を積んでおくので、メモリは O(深さ × 範囲) になる。
+/// 範囲は「削除するバイト数の上限」と「残せるバイト数の上限」の狭い方。ルートは削除できない。
+///
+/// 範囲が広すぎてメモリが MAX_DP_BYTES を超えるときは、確保する前にDPをやめ、
+/// 削除する組み合わせが少なければすべて試す。多ければエラーにする。
+pub fn plan_cleanup(
+    fs: &FileSystem,
+    disk: u64,
+    required: u64,
+    objective: Objective,
+) -> Result<CleanupPlan> {
+    let used = fs.node(FileSystem::ROOT).size();
+    let free = disk.saturating_sub(used);
+    let need = required.saturating_sub(free);
+    if need == 0 {
+        return Ok(CleanupPlan {
+            need,
+            deleted: Vec::new(),
+            freed: 0,
+        });
+    }
+
+    let knapsack = Knapsack::new(fs);
+    let Some((count, bound)) = knapsack.greedy(need) else {
+        bail!("can not free {} bytes", need);
+    };
+    // 1つで足りるもののうち、一番小さいもの
+    let single = (1..knapsack.order.len())
+        .filter(|&p| knapsack.sizes[p] >= need)
+        .min_by_key(|&p| knapsack.sizes[p]);
+
+    let positions = match (objective, single) {
+        (Objective::FewestItems, Some(p)) => Some(vec![p]),
+        (Objective::FewestItems, None) => knapsack.fewest_items(need, count),
+        (Objective::MinBytes, _) => {
+            let cap = single.map_or(bound, |p| knapsack.sizes[p].min(bound));
+            knapsack.min_bytes(need, cap)
+        }
+    };
+    let positions = match positions {
+        Some(positions) => positions,
+        None if knapsack.combinations() <= MAX_SEARCH_COMBINATIONS => {
+            knapsack.search(need, objective)
+        }
+        None => bail!(
+            "can not plan to free {} bytes: too many bytes and too many items to search",
+            need
+        ),
+    };
+
+    Ok(CleanupPlan {
+        need,
+        deleted: positions.iter().map(|&p| knapsack.order[p]).collect(),
+        freed: positions.iter().map(|&p| knapsack.sizes[p]).sum(),
+    })
+}
+
+/// どちらのバイト数を数えるか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Deleted,
+    Kept,
+}
+
+/// 1回の走査の設定。most が None なら削除した数は数えない。
+struct Scan {
+    side: Side,
+    limit: usize,
+    most: Option<usize>,
+}
+
+impl Scan {
+    fn layers(&self) -> usize {
+        self.most.map_or(1, |most| most + 1)
+    }
+
+    /// 1つ削除した後の層
+    fn next_layer(&self, layer: usize) -> Option<usize> {
+        match self.most {
+            None => Some(0),
+            Some(most) => (layer < most).then_some(layer + 1),
+        }
+    }
+}
+
+/// 深さ優先の順のノードと、その部分木の終わりの位置
+struct Knapsack {
+    order: Vec<NodeId>,
+    sizes: Vec<u64>,
+    end: Vec<usize>,
+    files: Vec<bool>,
+    /// その位置より前のファイルの合計
+    file_bytes: Vec<u64>,
+    /// ルートを除くディレクトリの入れ子の深さ(DPで積む状態の数)
+    depth: usize,
+}
+
+impl Knapsack {
+    fn new(fs: &FileSystem) -> Self {
+        let order = fs.walk();
+        let sizes: Vec<u64> = order.iter().map(|&id| fs.node(id).size()).collect();
+        let files: Vec<bool> = order.iter().map(|&id| !fs.node(id).is_dir()).collect();
+
+        let mut position = vec![0; order.len()];
+        for (p, &id) in order.iter().enumerate() {
+            position[id] = p;
+        }
+        // 部分木は連続して並ぶので、最後の子孫の次が終わり
+        let mut end: Vec<usize> = (1..=order.len()).collect();
+        for p in (1..order.len()).rev() {
+            let parent = position[fs.node(order[p]).parent.unwrap()];
+            end[parent] = end[parent].max(end[p]);
+        }
+
+        let mut file_bytes = vec![0];
+        for p in 0..order.len() {
+            let bytes = if files[p] { sizes[p] } else { 0 };
+            file_bytes.push(file_bytes[p] + bytes);
+        }
+
+        let mut levels = vec![0; order.len()];
+        for p in 1..order.len() {
+            levels[p] = levels[position[fs.node(order[p]).parent.unwrap()]] + 1;
+        }
+        let depth = (1..order.len())
+            .filter(|&p| !files[p])
+            .map(|p| levels[p])
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            order,
+            sizes,
+            end,
+            files,
+            file_bytes,
+            depth,
+        }
+    }
+
+    /// 大きい順に、互いに含み合わないものを need に届くまで選んだときの (数, 合計)
+    fn greedy(&self, need: u64) -> Option<(usize, u64)> {
+        let mut by_size: Vec<usize> = (1..self.order.len()).collect();
+        by_size.sort_by_key(|&p| Reverse(self.sizes[p]));
+
+        // 選んだ部分木の範囲
+        let mut taken: Vec<(usize, usize)> = Vec::new();
+        let mut sum = 0;
+        for p in by_size {
+            if sum >= need {
+                break;
+            }
+            if taken.iter().all(|&(a, b)| self.end[p] <= a || b <= p) {
+                taken.push((p, self.end[p]));
+                sum += self.sizes[p];
+            }
+        }
+        (sum >= need).then_some((taken.len(), sum))
+    }
+
+    /// 範囲 limit、layers 層のDPで使うメモリの見積もり(バイト)。
+    /// 今の状態と、ディレクトリごとに積む状態と、走査の結果。
+    fn dp_memory(&self, limit: u64, layers: usize) -> u64 {
+        let bits = limit.saturating_add(1).div_ceil(64).saturating_mul(8);
+        bits.saturating_mul(layers as u64)
+            .saturating_mul(self.depth as u64 + 2)
+    }
+
+    /// 互いに含み合わないものの選び方の数(何も選ばない場合も含む)。u64::MAX で頭打ち。
+    fn combinations(&self) -> u64 {
+        // ways[p] は位置 p の部分木の中での選び方の数
+        let mut ways = vec![1u64; self.order.len()];
+        for p in (0..self.order.len()).rev() {
+            if self.files[p] {
+                ways[p] = 2;
+                continue;
+            }
+            let mut inside = 1u64;
+            let mut q = p + 1;
+            while q < self.end[p] {
+                inside = inside.saturating_mul(ways[q]);
+                q = self.end[q];
+            }
+            // ルートは削除できない
+            ways[p] = if p == 0 {
+                inside
+            } else {
+                inside.saturating_add(1)
+            };
+        }
+        ways[0]
+    }
+
+    /// 互いに含み合わないものの選び方をすべて試す。
+    /// need に届いたらそれ以上は削除しない(数もバイト数も増えるだけなので)。
+    fn search(&self, need: u64, objective: Objective) -> Vec<usize> {
+        // (比べる値, 位置)
+        let mut best: Option<((usize, u64), Vec<usize>)> = None;
+        // (次の位置, 削除したバイト数, 削除した位置)
+        let mut stack = vec![(1, 0, Vec::new())];
+
+        while let Some((p, freed, chosen)) = stack.pop() {
+            if freed >= need {
+                let key = match objective {
+                    Objective::MinBytes => (0, freed),
+                    Objective::FewestItems => (chosen.len(), freed),
+                };
+                if best.as_ref().is_none_or(|(best, _)| key < *best) {
+                    best = Some((key, chosen));
+                }
+                continue;
+            }
+            if p == self.order.len() {
+                continue;
+            }
+
+            // 残す。ディレクトリなら中に入る
+            stack.push((p + 1, freed, chosen.clone()));
+            // 削除する
+            let mut chosen = chosen;
+            chosen.push(p);
+            stack.push((self.end[p], freed + self.sizes[p], chosen));
+        }
+
+        best.expect("greedy solution exists").1
+    }
+
+    /// 位置 p で (残す, 削除する) ときに増えるバイト数
+    fn weights(&self, side: Side, p: usize) -> (u64, u64) {
+        match side {
+            Side::Deleted => (0, self.sizes[p]),
+            Side::Kept if self.files[p] => (self.sizes[p], 0),
+            Side::Kept => (0, 0),
+        }
+    }
+
+    /// from から to まで何も削除しないときに増えるバイト数
+    fn kept(&self, side: Side, from: usize, to: usize) -> u64 {
+        match side {
+            Side::Deleted => 0,
+            Side::Kept => self.file_bytes[to] - self.file_bytes[from],
+        }
+    }
+
+    /// 位置 until までたどり、その位置の状態(層ごとのバイト数の集合)を返す。
+    /// observe(p, 状態) を途中の各位置で呼ぶ。
+    fn run(&self, scan: &Scan, until: usize, mut observe: impl FnMut(usize, &[Bits])) -> Vec<Bits> {
+        let mut state = vec![Bits::new(scan.limit + 1); scan.layers()];
+        state[0].set(0);
+        // (部分木の終わり, ディレクトリに入る前の状態, 削除したときに増えるバイト数)
+        let mut stack: Vec<(usize, Vec<Bits>, u64)> = Vec::new();
+
+        for p in 0..=until {
+            while stack.last().is_some_and(|&(end, ..)| end == p) {
+                let (_, before, delete) = stack.pop().unwrap();
+                for (layer, bits) in before.iter().enumerate() {
+                    if let Some(next) = scan.next_layer(layer) {
+                        state[next].or_shifted(bits, delete as usize);
+                    }
+                }
+            }
+            if p == until {
+                break;
+            }
+            observe(p, &state);
+
+            // ルートには入るだけ
+            if p == 0 {
+                continue;
+            }
+            let (keep, delete) = self.weights(scan.side, p);
+            if self.files[p] {
+                Self::step_file(scan, &mut state, keep as usize, delete as usize);
+            } else {
+                stack.push((self.end[p], state.clone(), delete));
+            }
+        }
+
+        state
+    }
+
+    /// ファイルを残すか削除したときの状態にその場で更新する
+    fn step_file(scan: &Scan, state: &mut [Bits], keep: usize, delete: usize) {
+        if scan.most.is_none() {
+            // keep と delete のどちらかは0
+            state[0].or_shifted_self(keep + delete);
+            return;
+        }
+        // 上の層から更新すれば、1つ下の層はまだ元のまま
+        for layer in (0..state.len()).rev() {
+            let (lower, upper) = state.split_at_mut(layer);
+            if keep > 0 {
+                upper[0].shift_up(keep);
+            }
+            if let Some(bits) = lower.last() {
+                upper[0].or_shifted(bits, delete);
+            }
+        }
+    }
+
+    /// 位置 q で s になったときの、直前に削除したものの候補。(位置, その位置でのバイト数)
+    fn candidates(
+        &self,
+        side: Side,
+        q: usize,
+        s: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (1..q).filter_map(move |p| {
+            if self.end[p] > q {
+                return None;
+            }
+            let (_, delete) = self.weights(side, p);
+            let before = (s as u64).checked_sub(delete + self.kept(side, self.end[p], q))?;
+            Some((p, before as usize))
+        })
+    }
+
+    /// need 以上で最小のバイト数になる位置。削除するバイト数は cap 以下。
+    /// 各バイト数が最初に作れた位置を覚えておき、後ろからたどる。
+    /// メモリが MAX_DP_BYTES を超えるならNone。
+    fn min_bytes(&self, need: u64, cap: u64) -> Option<Vec<usize>> {
+        let n = self.order.len();
+        let kept_limit = self.file_bytes[n] - need;
+        let (side, limit) = if kept_limit < cap {
+            (Side::Kept, kept_limit)
+        } else {
+            (Side::Deleted, cap)
+        };
+        // 最初に作れた位置は u32
+        let memory = self
+            .dp_memory(limit, 1)
+            .saturating_add(limit.saturating_add(1).saturating_mul(4));
+        if memory > MAX_DP_BYTES {
+            return None;
+        }
+        let scan = Scan {
+            side,
+            limit: limit as usize,
+            most: None,
+        };
+
+        // 削除した数を数えなければ、ある位置で作れるバイト数は後ろの位置でも作れる
+        let mut first = vec![u32::MAX; scan.limit + 1];
+        let mut seen = Bits::new(scan.limit + 1);
+        let last = self.run(&scan, n, |p, state| {
+            seen.mark(&state[0], |s| first[s] = p as u32)
+        });
+        seen.mark(&last[0], |s| first[s] = n as u32);
+
+        let found = match scan.side {
+            Side::Deleted => (need as usize..=scan.limit).find(|&s| last[0].get(s)),
+            Side::Kept => (0..=scan.limit).rev().find(|&s| last[0].get(s)),
+        };
+        let (mut q, mut s) = (n, found.expect("greedy solution is within the limit"));
+
+        let mut chosen = Vec::new();
+        while s as u64 != self.kept(scan.side, 0, q) {
+            let (p, before) = self
+                .candidates(scan.side, q, s)
+                .find(|&(p, before)| first[before] as usize <= p)
+                .expect("reachable state has a predecessor");
+            chosen.push(p);
+            (q, s) = (p, before);
+        }
+        chosen.reverse();
+        Some(chosen)
+    }
+
+    /// 最も少ない数で need 以上を削除し、同じ数なら削除するバイト数が最小になる位置。
+    /// 残すバイト数を削除した数(most まで)ごとの層で数え、1回の走査で最小の数を求める。
+    /// 選んだものは、1つずつ手前までもう一度走査してたどる。
+    /// 残すバイト数は増える一方なので、たどるときは今のバイト数までと残りの数だけ数える。
+    /// メモリが MAX_DP_BYTES を超えるならNone。
+    fn fewest_items(&self, need: u64, most: usize) -> Option<Vec<usize>> {
+        let n = self.order.len();
+        let limit = self.file_bytes[n] - need;
+        // 最後の状態を持ったまま、もう一度走査する
+        if self.dp_memory(limit, most + 1).saturating_mul(2) > MAX_DP_BYTES {
+            return None;
+        }
+        let scan = Scan {
+            side: Side::Kept,
+            limit: limit as usize,
+            most: Some(most),
+        };
+
+        let last = self.run(&scan, n, |_, _| {});
+        let (mut layer, mut s) = (1..=most)
+            .find_map(|layer| {
+                (0..=scan.limit)
+                    .rev()
+                    .find(|&s| last[layer].get(s))
+                    .map(|s| (layer, s))
+            })
+            .expect("greedy solution is within the limit");
+
+        let mut chosen = Vec::new();
+        let mut q = n;
+        while layer > 0 {
+            let mut before = vec![None; q];
+            for (p, b) in self.candidates(scan.side, q, s) {
+                before[p] = Some(b);
+            }
+            let mut reachable = vec![false; q];
+            let rescan = Scan {
+                side: scan.side,
+                limit: s,
+                most: Some(layer - 1),
+            };
+            self.run(&rescan, q, |p, state| {
+                if let Some(b) = before[p] {
+                    reachable[p] = state[layer - 1].get(b);
+                }
+            });
+
+            let p = (1..q)
+                .find(|&p| reachable[p])
+                .expect("reachable state has a predecessor");
+            chosen.push(p);
+            (q, s, layer) = (p, before[p].unwrap(), layer - 1);
+        }
+        chosen.reverse();
+        Some(chosen)
+    }
+}
+
+/// words を shift ビットずらしたときの i 番目の語
+fn shifted_word(words: &[u64], i: usize, shift: usize) -> u64 {
+    let (offset, bits) = (shift / 64, shift % 64);
+    if i < offset {
+        return 0;
+    }
+    let src = i - offset;
+    let mut value = words[src] << bits;
+    if bits > 0 && src > 0 {
+        value |= words[src - 1] >> (64 - bits);
+    }
+    value
+}
+
+/// 固定長のビット列
+#[derive(Clone)]
+struct Bits {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bits {
+    fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    fn get(&self, i: usize) -> bool {
+        i < self.len && self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    fn set(&mut self, i: usize) {
+        if i < self.len {
+            self.words[i / 64] |= 1 << (i % 64);
+        }
+    }
+
+    /// other を shift ビットずらして足す。長さを超えた分は捨てる。
+    fn or_shifted(&mut self, other: &Bits, shift: usize) {
+        for i in (shift / 64..self.words.len()).rev() {
+            self.words[i] |= shifted_word(&other.words, i, shift);
+        }
+        self.trim();
+    }
+
+    /// 自分を shift ビットずらして足す
+    fn or_shifted_self(&mut self, shift: usize) {
+        // 上の語から更新すれば、読む語はまだ元のまま
+        for i in (shift / 64..self.words.len()).rev() {
+            self.words[i] |= shifted_word(&self.words, i, shift);
+        }
+        self.trim();
+    }
+
+    /// shift ビットずらす
+    fn shift_up(&mut self, shift: usize) {
+        for i in (0..self.words.len()).rev() {
+            self.words[i] = shifted_word(&self.words, i, shift);
+        }
+        self.trim();
+    }
+
+    /// 端数のビットを消す
+    fn trim(&mut self) {
+        if !self.len.is_multiple_of(64) {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << (self.len % 64)) - 1;
+        }
+    }
+
+    /// other にあって self にないビットを足し、その位置で f を呼ぶ
+    fn mark(&mut self, other: &Bits, mut f: impl FnMut(usize)) {
+        for (i, (word, &value)) in self.words.iter_mut().zip(&other.words).enumerate() {
+            let mut added = value & !*word;
+            *word |= added;
+            while added != 0 {
+                f(i * 64 + added.trailing_zeros() as usize);
+                added &= added - 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use fastrand::Rng;
+
+    use super::*;
+    use crate::command::CommandParse;
+    use crate::transcript::{generate, Entry, Order};
+
+    fn build(text: &str) -> FileSystem {
+        let commands = text.as_bytes().parse_commands().unwrap();
+        FileSystem::from_commands(&commands).unwrap()
+    }
+
+    fn from_entry(entry: &Entry) -> FileSystem {
+        build(&generate(entry, Order::DepthFirst))
+    }
+
+    #[test]
+    fn test_plan_sample() {
+        let fs = build(include_str!("../data/sample.txt"));
+
+        let plan = plan_cleanup(&fs, 70000000, 30000000, Objective::MinBytes).unwrap();
+        assert_eq!(
+            plan.render(&fs),
+            indoc! {"
+                need: 8381165
+                delete /c.dat (file, size=8504156)
+                freed: 8504156 (1 items)
+            "}
+        );
+
+        let plan = plan_cleanup(&fs, 70000000, 30000000, Objective::FewestItems).unwrap();
+        assert_eq!(plan.freed, 8504156);
+
+        // 空きが足りていれば何もしない
+        let plan = plan_cleanup(&fs, 100000000, 30000000, Objective::MinBytes).unwrap();
+        assert_eq!(plan.deleted, vec![]);
+        assert!(plan_cleanup(&fs, 50000000, 60000000, Objective::MinBytes).is_err());
+    }
+
+    #[test]
+    fn test_objectives() {
+        let fs = build(indoc! {"
+            $ cd /
+            $ ls
+            dir a
+            5 b
+            6 c
+            $ cd a
+            $ ls
+            6 x
+            6 y
+        "});
+
+        // 空きはないので 11 を空ける
+        let plan = plan_cleanup(&fs, 23, 11, Objective::MinBytes).unwrap();
+        assert_eq!(plan.freed, 11);
+        assert_eq!(plan.deleted.len(), 2);
+        assert!(plan.deleted.contains(&fs.lookup("/b").unwrap()));
+
+        let plan = plan_cleanup(&fs, 23, 11, Objective::FewestItems).unwrap();
+        assert_eq!(plan.deleted, vec![fs.lookup("/a").unwrap()]);
+        assert_eq!(plan.freed, 12);
+
+        // 1つでは足りない
+        let plan = plan_cleanup(&fs, 23, 18, Objective::FewestItems).unwrap();
+        assert_eq!(plan.deleted.len(), 2);
+        assert_eq!(plan.freed, 18);
+    }
+
+    /// 選んだものが互いに含み合わず、合計が合っているか
+    fn check(fs: &FileSystem, plan: &CleanupPlan) {
+        assert!(plan.freed >= plan.need);
+        let sizes: u64 = plan.deleted.iter().map(|&id| fs.node(id).size()).sum();
+        assert_eq!(sizes, plan.freed);
+        for &id in &plan.deleted {
+            assert_ne!(id, FileSystem::ROOT);
+            let path = fs.path(id);
+            for &other in &plan.deleted {
+                assert!(other == id || !fs.path(other).starts_with(&path));
+            }
+        }
+    }
+
+    /// ファイルだけを選んで、残せる最大のバイト数
+    fn max_kept(fs: &FileSystem, limit: usize) -> u64 {
+        let mut reachable = vec![false; limit + 1];
+        reachable[0] = true;
+        for id in fs.find(|node| !node.is_dir()) {
+            let size = fs.node(id).size() as usize;
+            for s in (size..=limit).rev() {
+                reachable[s] |= reachable[s - size];
+            }
+        }
+        (0..=limit).rev().find(|&s| reachable[s]).unwrap() as u64
+    }
+
+    #[test]
+    fn test_plan_input() {
+        let fs = build(include_str!("../data/input.txt"));
+        let used = fs.node(FileSystem::ROOT).size();
+
+        let plan = plan_cleanup(&fs, 70000000, 30000000, Objective::MinBytes).unwrap();
+        check(&fs, &plan);
+        assert_eq!((plan.need, plan.freed), (389918, 389918));
+
+        // 一番大きいディレクトリでも足りない
+        let largest = fs.find(|_| true)[1..]
+            .iter()
+            .map(|&id| fs.node(id).size())
+            .max()
+            .unwrap();
+        let plan = plan_cleanup(&fs, 70000000, 69900000, Objective::MinBytes).unwrap();
+        check(&fs, &plan);
+        assert!(plan.need > largest);
+        let limit = (used - plan.need) as usize;
+        assert_eq!(plan.freed, used - max_kept(&fs, limit));
+
+        let fewest = plan_cleanup(&fs, 70000000, 69900000, Objective::FewestItems).unwrap();
+        check(&fs, &fewest);
+        assert!(fewest.deleted.len() < plan.deleted.len());
+        assert_eq!(fewest.deleted.len(), 9);
+        assert_eq!(fewest.freed, 40383851);
+    }
+
+    /// 互いに含み合わない選び方をすべて試し、(最小のバイト数, 最小の (数, バイト数)) を返す
+    fn brute_force(fs: &FileSystem, need: u64) -> Option<(u64, (usize, u64))> {
+        let nodes = &fs.walk()[1..];
+        let paths: Vec<_> = nodes.iter().map(|&id| fs.path(id)).collect();
+        // 祖先のマスク
+        let ancestors: Vec<u32> = paths
+            .iter()
+            .map(|path| {
+                (0..nodes.len())
+                    .filter(|&j| path != &paths[j] && path.starts_with(&paths[j]))
+                    .fold(0, |mask, j| mask | 1 << j)
+            })
+            .collect();
+
+        let mut best: Option<(u64, (usize, u64))> = None;
+        for mask in 0u32..1 << nodes.len() {
+            let chosen: Vec<usize> = (0..nodes.len()).filter(|&i| mask & 1 << i != 0).collect();
+            if chosen.iter().any(|&i| ancestors[i] & mask != 0) {
+                continue;
+            }
+            let freed: u64 = chosen.iter().map(|&i| fs.node(nodes[i]).size()).sum();
+            if freed < need {
+                continue;
+            }
+            let fewest = (chosen.len(), freed);
+            best = Some(match best {
+                None => (freed, fewest),
+                Some((bytes, items)) => (bytes.min(freed), items.min(fewest)),
+            });
+        }
+        best
+    }
+
+    #[test]
+    fn test_random_trees() {
+        let mut tested = 0;
+        for seed in 0..100 {
+            let fs = from_entry(&Entry::random(seed, 2, 2));
+            let used = fs.node(FileSystem::ROOT).size();
+            if used == 0 || fs.walk().len() > 15 {
+                continue;
+            }
+            tested += 1;
+
+            // 空きはないので required をそのまま空ける
+            for required in [1, used / 4, used / 2, used * 3 / 4, used, used + 1] {
+                let Some((bytes, items)) = brute_force(&fs, required) else {
+                    assert!(plan_cleanup(&fs, used, required, Objective::MinBytes).is_err());
+                    assert!(plan_cleanup(&fs, used, required, Objective::FewestItems).is_err());
+                    continue;
+                };
+
+                let plan = plan_cleanup(&fs, used, required, Objective::MinBytes).unwrap();
+                check(&fs, &plan);
+                assert_eq!(plan.freed, bytes, "seed {} required {}", seed, required);
+
+                let plan = plan_cleanup(&fs, used, required, Objective::FewestItems).unwrap();
+                check(&fs, &plan);
+                assert_eq!(
+                    (plan.deleted.len(), plan.freed),
+                    items,
+                    "seed {} required {}",
+                    seed,
+                    required
+                );
+
+                // DPできないときの全探索も同じ答えになる
+                let knapsack = Knapsack::new(&fs);
+                let freed = |positions: Vec<usize>| {
+                    let freed = positions.iter().map(|&p| knapsack.sizes[p]).sum::<u64>();
+                    (positions.len(), freed)
+                };
+                assert_eq!(
+                    freed(knapsack.search(required, Objective::MinBytes)).1,
+                    bytes
+                );
+                assert_eq!(
+                    freed(knapsack.search(required, Objective::FewestItems)),
+                    items
+                );
+            }
+        }
+        assert!(tested > 50, "{}", tested);
+    }
+
+    #[test]
+    fn test_huge_sizes() {
+        const GB: u64 = 1_000_000_000;
+        let fs = from_entry(&Entry::Dir(
+            "/".to_string(),
+            vec![
+                Entry::File("a".to_string(), 30 * GB),
+                Entry::File("b".to_string(), 25 * GB),
+                Entry::Dir(
+                    "d".to_string(),
+                    vec![
+                        Entry::File("c".to_string(), 20 * GB),
+                        Entry::File("e".to_string(), 15 * GB),
+                    ],
+                ),
+            ],
+        ));
+
+        // 範囲が広すぎるのでDPはせず、全部試す
+        let plan = plan_cleanup(&fs, 100 * GB, 80 * GB, Objective::MinBytes).unwrap();
+        check(&fs, &plan);
+        assert_eq!((plan.need, plan.freed), (70 * GB, 70 * GB));
+        let plan = plan_cleanup(&fs, 100 * GB, 80 * GB, Objective::FewestItems).unwrap();
+        check(&fs, &plan);
+        assert_eq!((plan.deleted.len(), plan.freed), (3, 70 * GB));
+
+        // 組み合わせも多すぎるときは、メモリを確保する前にエラーにする
+        let files = (0..40)
+            .map(|i| Entry::File(format!("f{}", i), 3 * GB + i))
+            .collect();
+        let fs = from_entry(&Entry::Dir("/".to_string(), files));
+        let err = plan_cleanup(&fs, 130 * GB, 80 * GB, Objective::MinBytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "can not plan to free 70000000780 bytes: too many bytes and too many items to search"
+        );
+        assert!(plan_cleanup(&fs, 130 * GB, 80 * GB, Objective::FewestItems).is_err());
+    }
+
+    #[test]
+    fn test_bits() {
+        let mut rng = Rng::with_seed(46);
+        let model = |bits: &Bits| (0..bits.len).map(|i| bits.get(i)).collect::<Vec<_>>();
+
+        for len in [1, 63, 64, 65, 130, 200] {
+            for _ in 0..50 {
+                let mut a = Bits::new(len);
+                let mut b = Bits::new(len);
+                for i in 0..len {
+                    if rng.bool() {
+                        a.set(i);
+                    }
+                    if rng.bool() {
+                        b.set(i);
+                    }
+                }
+                let shift = rng.usize(..len + 70);
+                let (ma, mb) = (model(&a), model(&b));
+                let shifted = |m: &[bool], i: usize| i >= shift && m[i - shift];
+
+                let mut bits = a.clone();
+                bits.or_shifted(&b, shift);
+                let expected: Vec<bool> = (0..len).map(|i| ma[i] || shifted(&mb, i)).collect();
+                assert_eq!(model(&bits), expected);
+
+                let mut bits = a.clone();
+                bits.or_shifted_self(shift);
+                let expected: Vec<bool> = (0..len).map(|i| ma[i] || shifted(&ma, i)).collect();
+                assert_eq!(model(&bits), expected);
+
+                let mut bits = a.clone();
+                bits.shift_up(shift);
+                let expected: Vec<bool> = (0..len).map(|i| shifted(&ma, i)).collect();
+                assert_eq!(model(&bits), expected);
+
+                let mut bits = a.clone();
+                let mut added = Vec::new();
+                bits.mark(&b, |i| added.push(i));
+                let expected: Vec<usize> = (0..len).filter(|&i| mb[i] && !ma[i]).collect();
+                assert_eq!(added, expected);
+                assert_eq!(
+                    model(&bits),
+                    (0..len).map(|i| ma[i] || mb[i]).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+}
@@ -1,19 +1,23 @@
+use std::error::Error;
+use std::fmt;
 use std::io::BufRead;
 
 use anyhow::anyhow;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{tag, take_till1};
 use nom::character::complete;
-use nom::character::complete::{alpha1, newline, space1};
-use nom::combinator::{map, opt, recognize, value};
+use nom::character::complete::{line_ending, space1};
+use nom::combinator::{all_consuming, map};
 use nom::IResult;
-use nom::multi::separated_list1;
-use nom::sequence::{preceded, separated_pair, tuple};
+use nom::multi::many0;
+use nom::sequence::{preceded, separated_pair};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Command {
     Cd(ChangeDir),
     Ls(Vec<LsOutput>),
+    // pwd の出力
+    Pwd(String),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -26,69 +30,241 @@ pub enum ChangeDir {
 
     // cd xxx
     MoveIn(String),
+
+    // cd /x/y, cd a/b, cd ../a など。1つずつの移動に分けたもの。
+    Path(Vec<ChangeDir>),
+}
+
+impl ChangeDir {
+    /// 1つずつの移動に分ける
+    pub fn steps(&self) -> Vec<ChangeDir> {
+        match self {
+            ChangeDir::Path(steps) => steps.clone(),
+            step => vec![step.clone()],
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum LsOutput {
     Dir(String),
     File(String, u64),
 }
 
+/// 解析できなかったコマンド。行は1始まり。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// cd, ls, pwd 以外のコマンド
+    UnknownCommand { line: usize, command: String },
+    /// コマンドや出力の書式が違う
+    Syntax { line: usize, text: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownCommand { line, command } => {
+                write!(f, "line {}: unknown command {:?}", line, command)
+            }
+            ParseError::Syntax { line, text } => {
+                write!(f, "line {}: unexpected {:?}", line, text)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// 解析できたコマンドと、できなかったコマンドのエラー
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Transcript {
+    pub commands: Vec<Command>,
+    pub errors: Vec<ParseError>,
+}
+
 pub trait CommandParse: BufRead {
+    /// エラーがあれば、最初のエラーで失敗する
     fn parse_commands(&mut self) -> anyhow::Result<Vec<Command>>;
+
+    /// 解析できないコマンドはエラーとして記録し、残りのコマンドを続けて解析する
+    fn parse_transcript(&mut self) -> anyhow::Result<Transcript>;
 }
 
 impl<R: BufRead> CommandParse for R {
     fn parse_commands(&mut self) -> anyhow::Result<Vec<Command>> {
+        let transcript = self.parse_transcript()?;
+
+        match transcript.errors.first() {
+            Some(e) => Err(anyhow!("failed to parse caused by {}", e)),
+            None => Ok(transcript.commands),
+        }
+    }
+
+    fn parse_transcript(&mut self) -> anyhow::Result<Transcript> {
         let mut buffer = String::new();
         let _ = self.read_to_string(&mut buffer)?;
 
-        return match separated_list1(newline, alt((cd, ls)))(&buffer) {
-            Ok((_, commands)) => Ok(commands),
-            Err(e) => Err(anyhow!("failed to parse caused by {}", e.to_string())),
+        let mut transcript = Transcript::default();
+        for (line, block) in blocks(&buffer) {
+            let (command, errors) = parse_block(line, block);
+            transcript.commands.extend(command);
+            transcript.errors.extend(errors);
+        }
+
+        Ok(transcript)
+    }
+}
+
+/// `$ ` で始まる行から、次の `$ ` の前までを1つのコマンドとして分ける。
+/// (始まりの行番号, コマンドと出力)
+fn blocks(text: &str) -> Vec<(usize, &str)> {
+    let mut blocks = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        // コマンドの前にある出力も、1つのブロックとしてエラーにする
+        if line.starts_with("$ ") || start.is_none() {
+            if let Some((line_no, begin)) = start {
+                blocks.push((line_no, &text[begin..offset]));
+            }
+            start = Some((i + 1, offset));
+        }
+        offset += line.len();
+    }
+    if let Some((line_no, begin)) = start {
+        blocks.push((line_no, &text[begin..]));
+    }
+
+    blocks
+}
+
+/// 1つのコマンドを解析する。ls の出力で読めない行があれば、
+/// その行だけをエラーにして、読めた行で Ls を作る。
+fn parse_block(line: usize, block: &str) -> (Option<Command>, Vec<ParseError>) {
+    let block = block.trim_end_matches(['\n', '\r']);
+
+    let Some(command_line) = block.lines().next().and_then(|l| l.strip_prefix("$ ")) else {
+        return (None, vec![syntax_error(line, block, block)]);
+    };
+    let name = command_line.split_whitespace().next().unwrap_or("");
+    if !["cd", "ls", "pwd"].contains(&name) {
+        let e = ParseError::UnknownCommand {
+            line,
+            command: name.to_string(),
         };
+        return (None, vec![e]);
+    }
+    if name == "ls" && command_line.trim_end() == "ls" {
+        return ls_lines(line, block);
+    }
+
+    let result = match alt((cd, ls, pwd))(block) {
+        Ok(("", command)) => Ok(command),
+        Ok((rest, _)) => Err(syntax_error(
+            line,
+            block,
+            rest.trim_start_matches(['\r', '\n']),
+        )),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(syntax_error(line, block, e.input)),
+        Err(nom::Err::Incomplete(_)) => Err(syntax_error(line, block, block)),
+    };
+    match result {
+        Ok(command) => (Some(command), Vec::new()),
+        Err(e) => (None, vec![e]),
+    }
+}
+
+/// ls の出力を1行ずつ読む
+fn ls_lines(line: usize, block: &str) -> (Option<Command>, Vec<ParseError>) {
+    let mut outputs = Vec::new();
+    let mut errors = Vec::new();
+    for (i, text) in block.lines().enumerate().skip(1) {
+        match all_consuming(ls_output)(text) {
+            Ok((_, output)) => outputs.push(output),
+            Err(_) => errors.push(ParseError::Syntax {
+                line: line + i,
+                text: text.to_string(),
+            }),
+        }
     }
+    (Some(Command::Ls(outputs)), errors)
+}
+
+/// rest はブロックの中で解析できなかった位置から後ろ
+fn syntax_error(line: usize, block: &str, rest: &str) -> ParseError {
+    let consumed = &block[..block.len() - rest.len()];
+    ParseError::Syntax {
+        line: line + consumed.matches('\n').count(),
+        text: rest.lines().next().unwrap_or("").to_string(),
+    }
+}
+
+/// 行の終わりまで(空は不可)
+fn rest_of_line(input: &str) -> IResult<&str, &str> {
+    take_till1(|c| c == '\n' || c == '\r')(input)
 }
 
 /// cdコマンド
 fn cd(input: &str) -> IResult<&str, Command> {
+    let (input, _) = tag("$ cd ")(input)?;
+    let (input, path) = rest_of_line(input)?;
+
+    Ok((input, Command::Cd(parse_path(path.trim_end()))))
+}
+
+fn parse_path(path: &str) -> ChangeDir {
     use ChangeDir::*;
 
-    let (input, _) = tag("$ cd ")(input)?;
-    let (input, cd) = alt((
-        value(Root, tag("/")),
-        value(MoveOut, tag("..")),
-        map(alpha1, |s: &str| MoveIn(s.to_string())),
-    ))(input)?;
+    match path {
+        "/" => return Root,
+        ".." => return MoveOut,
+        name if !name.contains('/') && name != "." => return MoveIn(name.to_string()),
+        _ => {}
+    }
 
-    Ok((input, Command::Cd(cd)))
+    let mut steps = Vec::new();
+    if path.starts_with('/') {
+        steps.push(Root);
+    }
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => steps.push(MoveOut),
+            name => steps.push(MoveIn(name.to_string())),
+        }
+    }
+    Path(steps)
 }
 
-/// lsコマンド
-fn ls<'a>(input: &'a str) -> IResult<&'a str, Command> {
+/// pwdコマンド
+fn pwd(input: &str) -> IResult<&str, Command> {
+    let (input, _) = tag("$ pwd")(input)?;
+    let (input, path) = preceded(line_ending, rest_of_line)(input)?;
+
+    Ok((input, Command::Pwd(path.to_string())))
+}
+
+/// lsコマンド。空のディレクトリなら出力はない。
+fn ls(input: &str) -> IResult<&str, Command> {
     let (input, _) = tag("$ ls")(input)?;
-    let (input, _) = newline(input)?;
+    let (input, outputs) = many0(preceded(line_ending, ls_output))(input)?;
 
-    let dir = |i: &'a str| {
-        map(preceded(tag("dir "), alpha1), |s: &str| {
-            LsOutput::Dir(s.to_string())
-        })(i)
-    };
+    Ok((input, Command::Ls(outputs)))
+}
 
-    let file = |i: &'a str| {
-        map(
-            separated_pair(
-                complete::u64,
-                space1,
-                recognize(tuple((alpha1, opt(tuple((tag("."), alpha1)))))),
-            ),
-            |(size, filename): (u64, &str)| LsOutput::File(filename.to_string(), size),
-        )(i)
-    };
+/// lsの出力の1行
+fn ls_output(input: &str) -> IResult<&str, LsOutput> {
+    let dir = map(preceded(tag("dir "), rest_of_line), |s: &str| {
+        LsOutput::Dir(s.to_string())
+    });
 
-    let (input, outputs) = separated_list1(newline, alt((dir, file)))(input)?;
+    let file = map(
+        separated_pair(complete::u64, space1, rest_of_line),
+        |(size, filename): (u64, &str)| LsOutput::File(filename.to_string(), size),
+    );
 
-    Ok((input, Command::Ls(outputs)))
+    alt((dir, file))(input)
 }
 
 #[cfg(test)]
@@ -108,6 +284,32 @@ mod test {
             cd("$ cd somewhere"),
             Ok(("", Cd(MoveIn("somewhere".to_string()))))
         );
+        assert_eq!(
+            cd("$ cd my-dir.v2.0"),
+            Ok(("", Cd(MoveIn("my-dir.v2.0".to_string()))))
+        );
+        assert_eq!(
+            cd("$ cd /x/y"),
+            Ok((
+                "",
+                Cd(Path(vec![
+                    Root,
+                    MoveIn("x".to_string()),
+                    MoveIn("y".to_string())
+                ]))
+            ))
+        );
+        assert_eq!(
+            cd("$ cd ../a/./b/"),
+            Ok((
+                "",
+                Cd(Path(vec![
+                    MoveOut,
+                    MoveIn("a".to_string()),
+                    MoveIn("b".to_string())
+                ]))
+            ))
+        );
     }
 
     #[test]
@@ -134,6 +336,18 @@ mod test {
         assert_eq!(nodes[2], LsOutput::File("f".to_string(), 29116));
         assert_eq!(nodes[3], LsOutput::File("c.dat".to_string(), 8504156));
         assert_eq!(nodes[4], LsOutput::Dir("d".to_string()));
+
+        // 数字やハイフン、複数のドットを含む名前、空のディレクトリ
+        let (_, nodes) = ls("$ ls\ndir 2022-12\n42 archive.tar.gz\n7 my file").unwrap();
+        assert_eq!(
+            nodes,
+            Command::Ls(vec![
+                LsOutput::Dir("2022-12".to_string()),
+                LsOutput::File("archive.tar.gz".to_string(), 42),
+                LsOutput::File("my file".to_string(), 7),
+            ])
+        );
+        assert_eq!(ls("$ ls"), Ok(("", Command::Ls(vec![]))));
     }
 
     #[test]
@@ -157,5 +371,76 @@ mod test {
         );
         assert_eq!(commands[2], Command::Cd(ChangeDir::MoveIn("a".to_string())));
         assert_eq!(commands[6], Command::Cd(ChangeDir::MoveOut));
+
+        // 改行が CRLF でも同じ
+        let crlf = include_str!("../data/sample.txt").replace('\n', "\r\n");
+        assert_eq!(crlf.as_bytes().parse_commands().unwrap(), commands);
+        let crlf = "$ pwd\r\n/\r\n$ ls\r\ndir a\r\n";
+        assert_eq!(
+            crlf.as_bytes().parse_commands().unwrap(),
+            vec![
+                Command::Pwd("/".to_string()),
+                Command::Ls(vec![LsOutput::Dir("a".to_string())]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_transcript() {
+        let text = indoc! {r#"
+            $ cd /
+            $ pwd
+            /
+            $ rm -rf a
+            $ ls
+            dir a
+            oops
+            2 c
+            $ cd a/b
+            $ cd
+            $ ls
+            1 x
+        "#};
+
+        let transcript = text.as_bytes().parse_transcript().unwrap();
+        assert_eq!(
+            transcript.commands,
+            vec![
+                Command::Cd(ChangeDir::Root),
+                Command::Pwd("/".to_string()),
+                Command::Ls(vec![
+                    LsOutput::Dir("a".to_string()),
+                    LsOutput::File("c".to_string(), 2)
+                ]),
+                Command::Cd(ChangeDir::Path(vec![
+                    ChangeDir::MoveIn("a".to_string()),
+                    ChangeDir::MoveIn("b".to_string())
+                ])),
+                Command::Ls(vec![LsOutput::File("x".to_string(), 1)]),
+            ]
+        );
+        assert_eq!(
+            transcript.errors,
+            vec![
+                ParseError::UnknownCommand {
+                    line: 4,
+                    command: "rm".to_string()
+                },
+                ParseError::Syntax {
+                    line: 7,
+                    text: "oops".to_string()
+                },
+                ParseError::Syntax {
+                    line: 10,
+                    text: "$ cd".to_string()
+                },
+            ]
+        );
+
+        let e = text.as_bytes().parse_commands().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "failed to parse caused by line 4: unknown command \"rm\""
+        );
     }
 }
@@ -1,7 +1,9 @@
-use std::collections::HashMap;
 use std::path::PathBuf;
 
-use crate::command::{ChangeDir, Command, LsOutput};
+use anyhow::Result;
+
+use crate::command::Command;
+use crate::filesystem::FileSystem;
 
 #[derive(Debug)]
 pub struct Directory {
@@ -10,78 +12,21 @@ pub struct Directory {
 }
 
 pub trait IntoDirectories {
-    fn into_directories(self) -> Vec<Directory>;
+    /// 記録が食い違っていれば(同じディレクトリの ls の結果が違う、pwd が合わないなど)エラー
+    fn into_directories(self) -> Result<Vec<Directory>>;
 }
 
 impl IntoDirectories for Vec<Command> {
-    fn into_directories(self) -> Vec<Directory> {
-        // ディレクトリ単体のサイズマップを取得
-        let directory_sizes = size_of_dirs(&self);
-
-        directory_sizes
-            .keys()
-            .map(|p| {
-                // ディレクトリ配下のファイルサイズを合算
-                let size = directory_sizes
-                    .iter()
-                    .filter_map(
-                        |(dir, size)| {
-                            if dir.starts_with(p) {
-                                Some(size)
-                            } else {
-                                None
-                            }
-                        },
-                    )
-                    .sum();
-
-                Directory {
-                    path: p.clone(),
-                    size,
-                }
-            })
-            .collect()
+    fn into_directories(self) -> Result<Vec<Directory>> {
+        // ファイルシステムの木を作り、ディレクトリごとの合計サイズを取得
+        Ok(FileSystem::from_commands(&self)?.du())
     }
 }
 
-/// {dir: size_of_files, ...}
-fn size_of_dirs(commands: &[Command]) -> HashMap<PathBuf, u64> {
-    let mut pwd = PathBuf::new();
-    let mut directory_sizes = HashMap::new();
-
-    for cmd in commands {
-        match cmd {
-            Command::Cd(cd) => match cd {
-                ChangeDir::Root => {
-                    pwd = PathBuf::from("/");
-                }
-                ChangeDir::MoveOut => {
-                    let parent = pwd.parent().expect("failed to get parent directory");
-                    pwd = parent.to_path_buf();
-                }
-                ChangeDir::MoveIn(dir) => {
-                    pwd.push(dir);
-                }
-            },
-            Command::Ls(files) => {
-                let size_of_files = files
-                    .iter()
-                    .filter_map(|output| match output {
-                        LsOutput::File(_, size) => Some(size),
-                        _ => None,
-                    })
-                    .sum();
-
-                directory_sizes.insert(pwd.clone(), size_of_files);
-            }
-        }
-    }
-
-    directory_sizes
-}
-
 #[cfg(test)]
 mod tests {
+    use indoc::indoc;
+
     use crate::command::CommandParse;
 
     use super::*;
@@ -92,7 +37,8 @@ mod tests {
             .as_bytes()
             .parse_commands()
             .unwrap()
-            .into_directories();
+            .into_directories()
+            .unwrap();
 
         dirs.sort_by_key(|d| d.path.clone());
 
@@ -110,4 +56,23 @@ mod tests {
         assert_eq!(dirs[3].path, PathBuf::from("/d"));
         assert_eq!(dirs[3].size, 24933642);
     }
+
+    #[test]
+    fn test_inconsistent() {
+        let commands = indoc! {"
+            $ cd /
+            $ ls
+            dir a
+            $ ls
+            dir b
+        "}
+        .as_bytes()
+        .parse_commands()
+        .unwrap();
+        let e = commands.into_directories().unwrap_err();
+        assert_eq!(e.to_string(), "inconsistent ls: /");
+
+        let commands = "$ cd a\n$ pwd\n/b\n".as_bytes().parse_commands().unwrap();
+        assert!(commands.into_directories().is_err());
+    }
 }
@@ -0,0 +1,415 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::command::{ChangeDir, Command, LsOutput};
+use crate::directory::Directory;
+
+/// ノードの番号。FileSystem の中の位置。
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    Dir(Vec<NodeId>),
+    File,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub kind: NodeKind,
+    // ファイルはそのサイズ、ディレクトリは配下の合計
+    size: u64,
+}
+
+impl Node {
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn is_dir(&self) -> bool {
+        matches!(self.kind, NodeKind::Dir(_))
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        match &self.kind {
+            NodeKind::Dir(children) => children,
+            NodeKind::File => &[],
+        }
+    }
+}
+
+/// コマンドの結果から作ったファイルシステムの木。
+/// ノードは配列(アリーナ)に持ち、親は必ず子より前にある。
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+    // ノードごとの、名前から子への表。ファイルは空。
+    names: Vec<HashMap<String, NodeId>>,
+}
+
+impl FileSystem {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                parent: None,
+                kind: NodeKind::Dir(Vec::new()),
+                size: 0,
+            }],
+            names: vec![HashMap::new()],
+        }
+    }
+
+    pub fn from_commands(commands: &[Command]) -> Result<Self> {
+        let mut fs = Self::new();
+        let mut pwd = Self::ROOT;
+        // ls したディレクトリとその中身。同じディレクトリの ls は同じ結果になること。
+        let mut listings: HashMap<NodeId, BTreeSet<NodeId>> = HashMap::new();
+
+        for cmd in commands {
+            match cmd {
+                Command::Cd(cd) => {
+                    for step in cd.steps() {
+                        pwd = fs.change_dir(pwd, &step)?;
+                    }
+                }
+                Command::Ls(outputs) => {
+                    let entries = outputs
+                        .iter()
+                        .map(|output| match output {
+                            LsOutput::Dir(name) => fs.add(pwd, name, None),
+                            LsOutput::File(name, size) => fs.add(pwd, name, Some(*size)),
+                        })
+                        .collect::<Result<BTreeSet<_>>>()?;
+
+                    match listings.get(&pwd) {
+                        Some(listed) if *listed != entries => {
+                            bail!("inconsistent ls: {}", fs.path(pwd).display())
+                        }
+                        Some(_) => {}
+                        None => {
+                            listings.insert(pwd, entries);
+                        }
+                    }
+                }
+                Command::Pwd(path) => {
+                    if Path::new(path) != fs.path(pwd) {
+                        bail!(
+                            "pwd printed {} but current directory is {}",
+                            path,
+                            fs.path(pwd).display()
+                        );
+                    }
+                }
+            }
+        }
+
+        fs.compute_sizes();
+        Ok(fs)
+    }
+
+    fn change_dir(&mut self, pwd: NodeId, step: &ChangeDir) -> Result<NodeId> {
+        match step {
+            ChangeDir::Root => Ok(Self::ROOT),
+            ChangeDir::MoveOut => self.nodes[pwd]
+                .parent
+                .ok_or_else(|| anyhow!("cd .. at root directory")),
+            // ls していないディレクトリにも入れる
+            ChangeDir::MoveIn(name) => self.add(pwd, name, None),
+            ChangeDir::Path(steps) => steps
+                .iter()
+                .try_fold(pwd, |pwd, step| self.change_dir(pwd, step)),
+        }
+    }
+
+    /// ディレクトリ(size が None)かファイルを追加する。すでにあればそれを返す。
+    fn add(&mut self, parent: NodeId, name: &str, size: Option<u64>) -> Result<NodeId> {
+        if let Some(id) = self.child(parent, name) {
+            let node = &self.nodes[id];
+            match (node.is_dir(), size) {
+                (true, None) => return Ok(id),
+                (false, Some(size)) if node.size == size => return Ok(id),
+                _ => bail!("conflicting entry: {}", self.path(id).display()),
+            }
+        }
+
+        if !self.nodes[parent].is_dir() {
+            bail!("not a directory: {}", self.path(parent).display());
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(parent),
+            kind: match size {
+                Some(_) => NodeKind::File,
+                None => NodeKind::Dir(Vec::new()),
+            },
+            size: size.unwrap_or(0),
+        });
+        self.names.push(HashMap::new());
+        if let NodeKind::Dir(children) = &mut self.nodes[parent].kind {
+            children.push(id);
+        }
+        self.names[parent].insert(name.to_string(), id);
+        Ok(id)
+    }
+
+    /// 子は親より後ろにあるので、後ろから親に足していけば1回で求まる
+    fn compute_sizes(&mut self) {
+        for node in self.nodes.iter_mut().filter(|node| node.is_dir()) {
+            node.size = 0;
+        }
+        for id in (1..self.nodes.len()).rev() {
+            let size = self.nodes[id].size;
+            let parent = self.nodes[id].parent.unwrap();
+            self.nodes[parent].size += size;
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn child(&self, parent: NodeId, name: &str) -> Option<NodeId> {
+        self.names[parent].get(name).copied()
+    }
+
+    /// "/a/e" のような絶対パスのノード
+    pub fn lookup(&self, path: impl AsRef<Path>) -> Option<NodeId> {
+        path.as_ref()
+            .components()
+            .try_fold(Self::ROOT, |id, component| match component {
+                Component::RootDir => Some(Self::ROOT),
+                Component::Normal(name) => self.child(id, name.to_str()?),
+                _ => None,
+            })
+    }
+
+    pub fn path(&self, id: NodeId) -> PathBuf {
+        let mut names = Vec::new();
+        let mut current = Some(id);
+        while let Some(id) = current {
+            names.push(self.nodes[id].name.as_str());
+            current = self.nodes[id].parent;
+        }
+        names.iter().rev().collect()
+    }
+
+    /// 深さ優先(名前順)でたどった順のノード
+    pub fn walk(&self) -> Vec<NodeId> {
+        let mut order = Vec::new();
+        let mut stack = vec![Self::ROOT];
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.sorted_children(id).into_iter().rev());
+        }
+        order
+    }
+
+    fn sorted_children(&self, id: NodeId) -> Vec<NodeId> {
+        let mut children = self.nodes[id].children().to_vec();
+        children.sort_by(|&a, &b| self.nodes[a].name.cmp(&self.nodes[b].name));
+        children
+    }
+
+    /// `du` のように、すべてのディレクトリと合計サイズ。子が親より先に並ぶ。
+    pub fn du(&self) -> Vec<Directory> {
+        self.walk()
+            .into_iter()
+            .rev()
+            .filter(|&id| self.nodes[id].is_dir())
+            .map(|id| Directory {
+                path: self.path(id),
+                size: self.nodes[id].size,
+            })
+            .collect()
+    }
+
+    /// 条件に合うノードを、深さ優先の順に返す
+    pub fn find(&self, predicate: impl Fn(&Node) -> bool) -> Vec<NodeId> {
+        self.walk()
+            .into_iter()
+            .filter(|&id| predicate(&self.nodes[id]))
+            .collect()
+    }
+
+    /// 問題文の `- / (dir)` の形式
+    pub fn tree(&self) -> String {
+        let mut lines = String::new();
+        let mut stack = vec![(Self::ROOT, 0)];
+        while let Some((id, depth)) = stack.pop() {
+            let node = &self.nodes[id];
+            let indent = "  ".repeat(depth);
+            match node.kind {
+                NodeKind::Dir(_) => lines.push_str(&format!("{}- {} (dir)\n", indent, node.name)),
+                NodeKind::File => lines.push_str(&format!(
+                    "{}- {} (file, size={})\n",
+                    indent, node.name, node.size
+                )),
+            }
+            stack.extend(
+                self.sorted_children(id)
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, depth + 1)),
+            );
+        }
+        lines
+    }
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::command::CommandParse;
+
+    fn sample() -> FileSystem {
+        let commands = include_str!("../data/sample.txt")
+            .as_bytes()
+            .parse_commands()
+            .unwrap();
+        FileSystem::from_commands(&commands).unwrap()
+    }
+
+    #[test]
+    fn test_tree() {
+        assert_eq!(
+            sample().tree(),
+            indoc! {"
+                - / (dir)
+                  - a (dir)
+                    - e (dir)
+                      - i (file, size=584)
+                    - f (file, size=29116)
+                    - g (file, size=2557)
+                    - h.lst (file, size=62596)
+                  - b.txt (file, size=14848514)
+                  - c.dat (file, size=8504156)
+                  - d (dir)
+                    - d.ext (file, size=5626152)
+                    - d.log (file, size=8033020)
+                    - j (file, size=4060174)
+                    - k (file, size=7214296)
+            "}
+        );
+    }
+
+    #[test]
+    fn test_du() {
+        let fs = sample();
+        let du: Vec<(String, u64)> = fs
+            .du()
+            .into_iter()
+            .map(|d| (d.path.display().to_string(), d.size))
+            .collect();
+        assert_eq!(
+            du,
+            vec![
+                ("/d".to_string(), 24933642),
+                ("/a/e".to_string(), 584),
+                ("/a".to_string(), 94853),
+                ("/".to_string(), 48381165),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let fs = sample();
+        let paths =
+            |ids: Vec<NodeId>| -> Vec<PathBuf> { ids.iter().map(|&id| fs.path(id)).collect() };
+
+        assert_eq!(
+            paths(fs.find(|node| node.name.starts_with('d'))),
+            vec![
+                PathBuf::from("/d"),
+                PathBuf::from("/d/d.ext"),
+                PathBuf::from("/d/d.log")
+            ]
+        );
+        assert_eq!(
+            paths(fs.find(|node| !node.is_dir() && node.size() < 10000)),
+            vec![PathBuf::from("/a/e/i"), PathBuf::from("/a/g")]
+        );
+
+        let e = fs.lookup("/a/e").unwrap();
+        assert_eq!(fs.node(e).size(), 584);
+        assert_eq!(fs.lookup("/a/x"), None);
+        assert_eq!(fs.find(|_| true).len(), 14);
+    }
+
+    #[test]
+    fn test_conflict() {
+        let commands = indoc! {"
+            $ cd /
+            $ ls
+            1 a
+            $ cd a
+        "}
+        .as_bytes()
+        .parse_commands()
+        .unwrap();
+        assert!(FileSystem::from_commands(&commands).is_err());
+
+        let commands = "$ cd /\n$ cd ..".as_bytes().parse_commands().unwrap();
+        assert!(FileSystem::from_commands(&commands).is_err());
+    }
+
+    #[test]
+    fn test_transcript_checks() {
+        let build = |text: &str| {
+            let commands = text.as_bytes().parse_commands().unwrap();
+            FileSystem::from_commands(&commands)
+        };
+
+        let fs = build(indoc! {"
+            $ cd /x/y
+            $ pwd
+            /x/y
+            $ ls
+            10 a.tar.gz
+            $ cd ../../x/y
+            $ ls
+            10 a.tar.gz
+        "})
+        .unwrap();
+        assert_eq!(fs.node(FileSystem::ROOT).size(), 10);
+        assert!(fs.lookup("/x/y/a.tar.gz").is_some());
+
+        // 同じディレクトリの ls の結果が違う
+        assert!(build("$ cd /\n$ ls\n1 a\n$ ls\n1 a\n2 b\n").is_err());
+        // pwd が違う
+        assert!(build("$ cd /a\n$ pwd\n/b\n").is_err());
+    }
+
+    #[test]
+    fn test_flat_directory() {
+        // 1つのディレクトリに多くのファイルがあっても、名前は表で引く
+        let n = 100000;
+        let mut text = "$ cd /\n$ ls\n".to_string();
+        for i in 0..n {
+            text.push_str(&format!("{} f{}\n", i, i));
+        }
+        let commands = text.as_bytes().parse_commands().unwrap();
+        let fs = FileSystem::from_commands(&commands).unwrap();
+
+        assert_eq!(fs.node(FileSystem::ROOT).children().len(), n);
+        assert_eq!(fs.node(FileSystem::ROOT).size(), (n * (n - 1) / 2) as u64);
+        let id = fs.child(FileSystem::ROOT, "f99999").unwrap();
+        assert_eq!(fs.node(id).size(), 99999);
+    }
+}
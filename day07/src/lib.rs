@@ -1,2 +1,5 @@
+pub mod cleanup;
 pub mod command;
 pub mod directory;
+pub mod filesystem;
+pub mod transcript;
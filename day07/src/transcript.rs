@@ -0,0 +1,321 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use fastrand::Rng;
+
+/// 端末の記録を作るためのディレクトリの木
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    Dir(String, Vec<Entry>),
+    File(String, u64),
+}
+
+impl Entry {
+    /// ディスク上のディレクトリを読み込む。名前は "/" になる。
+    /// 中身は名前順に並べ、ファイルとディレクトリ以外(シンボリックリンクなど)は読み飛ばす。
+    pub fn from_disk(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Entry::Dir("/".to_string(), read_dir(path.as_ref())?))
+    }
+
+    /// シード付きの乱数で作った木。各ディレクトリに最大 width 個のディレクトリとファイルを置く。
+    pub fn random(seed: u64, depth: usize, width: usize) -> Self {
+        let mut rng = Rng::with_seed(seed);
+        Entry::Dir("/".to_string(), random_entries(&mut rng, depth, width))
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Entry::Dir(name, _) | Entry::File(name, _) => name,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        match self {
+            Entry::Dir(_, entries) => entries.iter().map(|e| e.size()).sum(),
+            Entry::File(_, size) => *size,
+        }
+    }
+
+    /// すべてのディレクトリのパスと合計サイズ
+    pub fn directory_sizes(&self) -> Vec<(PathBuf, u64)> {
+        directories(self)
+            .into_iter()
+            .map(|(path, dir)| (path, dir.size()))
+            .collect()
+    }
+}
+
+fn read_dir(path: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    for dir_entry in fs::read_dir(path)? {
+        let dir_entry = dir_entry?;
+        let name = dir_entry.file_name().to_string_lossy().to_string();
+        let file_type = dir_entry.file_type()?;
+        if file_type.is_dir() {
+            entries.push(Entry::Dir(name, read_dir(&dir_entry.path())?));
+        } else if file_type.is_file() {
+            entries.push(Entry::File(name, dir_entry.metadata()?.len()));
+        }
+    }
+    entries.sort_by(|a, b| a.name().cmp(b.name()));
+    Ok(entries)
+}
+
+fn random_entries(rng: &mut Rng, depth: usize, width: usize) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for i in 0..rng.usize(..=width) {
+        entries.push(Entry::File(
+            format!("f{}.{}", i, ["txt", "tar.gz", "log"][rng.usize(..3)]),
+            rng.usize(..300_000) as u64 + 1,
+        ));
+    }
+    if depth > 0 {
+        for i in 0..rng.usize(..=width) {
+            entries.push(Entry::Dir(
+                format!("d{}-{}", depth, i),
+                random_entries(rng, depth - 1, width),
+            ));
+        }
+    }
+    entries
+}
+
+/// ディレクトリをたどる順番
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// 問題の入力と同じく、`cd name` と `cd ..` で深さ優先にたどる
+    DepthFirst,
+    /// 浅い順に、`cd /a/b` の絶対パスで移動する
+    BreadthFirst,
+    /// 乱数の順に、相対パスか絶対パスの短い方で移動する。
+    /// revisits 回、すでに見たディレクトリをもう一度 ls する。
+    Random { seed: u64, revisits: usize },
+}
+
+/// `$ cd` と `$ ls` の記録を作る
+pub fn generate(root: &Entry, order: Order) -> String {
+    let mut lines = vec!["$ cd /".to_string()];
+
+    match order {
+        Order::DepthFirst => depth_first(root, &mut lines),
+        Order::BreadthFirst => {
+            let mut queue = VecDeque::from([(PathBuf::from("/"), root)]);
+            while let Some((path, dir)) = queue.pop_front() {
+                if path != Path::new("/") {
+                    lines.push(format!("$ cd {}", path.display()));
+                }
+                ls(dir, &mut lines);
+                for entry in children(dir) {
+                    if let Entry::Dir(name, _) = entry {
+                        queue.push_back((path.join(name), entry));
+                    }
+                }
+            }
+        }
+        Order::Random { seed, revisits } => {
+            let mut rng = Rng::with_seed(seed);
+            let mut visits = directories(root);
+            // 最初の / はそのまま、残りを混ぜる
+            for i in (2..visits.len()).rev() {
+                let j = 1 + rng.usize(..i);
+                visits.swap(i, j);
+            }
+            for _ in 0..revisits {
+                let (path, dir) = visits[rng.usize(..visits.len())].clone();
+                let at = 1 + rng.usize(..visits.len());
+                visits.insert(at, (path, dir));
+            }
+
+            let mut pwd = PathBuf::from("/");
+            for (path, dir) in visits {
+                if path != pwd {
+                    lines.push(format!("$ cd {}", shortest_cd(&pwd, &path)));
+                    pwd = path;
+                }
+                ls(dir, &mut lines);
+            }
+        }
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn children(dir: &Entry) -> &[Entry] {
+    match dir {
+        Entry::Dir(_, entries) => entries,
+        Entry::File(..) => &[],
+    }
+}
+
+fn depth_first(dir: &Entry, lines: &mut Vec<String>) {
+    ls(dir, lines);
+    for entry in children(dir) {
+        if let Entry::Dir(name, _) = entry {
+            lines.push(format!("$ cd {}", name));
+            depth_first(entry, lines);
+            lines.push("$ cd ..".to_string());
+        }
+    }
+}
+
+fn ls(dir: &Entry, lines: &mut Vec<String>) {
+    lines.push("$ ls".to_string());
+    for entry in children(dir) {
+        match entry {
+            Entry::Dir(name, _) => lines.push(format!("dir {}", name)),
+            Entry::File(name, size) => lines.push(format!("{} {}", size, name)),
+        }
+    }
+}
+
+/// 深さ優先の順のディレクトリとそのパス
+fn directories(root: &Entry) -> Vec<(PathBuf, &Entry)> {
+    let mut dirs = Vec::new();
+    let mut stack = vec![(PathBuf::from("/"), root)];
+    while let Some((path, dir)) = stack.pop() {
+        for entry in children(dir).iter().rev() {
+            if let Entry::Dir(name, _) = entry {
+                stack.push((path.join(name), entry));
+            }
+        }
+        dirs.push((path, dir));
+    }
+    dirs
+}
+
+/// from から to への相対パスと絶対パスの短い方
+fn shortest_cd(from: &Path, to: &Path) -> String {
+    let from: Vec<_> = from.components().collect();
+    let to_components: Vec<_> = to.components().collect();
+    let common = from
+        .iter()
+        .zip(&to_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut segments = vec!["..".to_string(); from.len() - common];
+    segments.extend(
+        to_components[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().to_string()),
+    );
+    let relative = segments.join("/");
+    let absolute = to.display().to_string();
+
+    if relative.is_empty() || relative.len() >= absolute.len() {
+        absolute
+    } else {
+        relative
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::command::CommandParse;
+    use crate::directory::IntoDirectories;
+
+    const ORDERS: [Order; 4] = [
+        Order::DepthFirst,
+        Order::BreadthFirst,
+        Order::Random {
+            seed: 1,
+            revisits: 0,
+        },
+        Order::Random {
+            seed: 7,
+            revisits: 20,
+        },
+    ];
+
+    fn parsed_sizes(transcript: &str) -> HashMap<PathBuf, u64> {
+        transcript
+            .as_bytes()
+            .parse_commands()
+            .unwrap()
+            .into_directories()
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.path, d.size))
+            .collect()
+    }
+
+    /// ディスクから直接求めたサイズ
+    fn disk_sizes(root: &Path, path: &Path, sizes: &mut HashMap<PathBuf, u64>) -> u64 {
+        let mut total = 0;
+        for entry in fs::read_dir(root.join(path)).unwrap() {
+            let entry = entry.unwrap();
+            let metadata = fs::symlink_metadata(entry.path()).unwrap();
+            if metadata.is_dir() {
+                total += disk_sizes(root, &path.join(entry.file_name()), sizes);
+            } else if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+        sizes.insert(Path::new("/").join(path), total);
+        total
+    }
+
+    #[test]
+    fn test_generate_sample() {
+        let root = Entry::Dir(
+            "/".to_string(),
+            vec![
+                Entry::Dir("a".to_string(), vec![Entry::File("i".to_string(), 584)]),
+                Entry::File("b.txt".to_string(), 14848514),
+            ],
+        );
+        assert_eq!(
+            generate(&root, Order::DepthFirst),
+            "$ cd /\n$ ls\ndir a\n14848514 b.txt\n$ cd a\n$ ls\n584 i\n$ cd ..\n"
+        );
+        assert_eq!(
+            shortest_cd(Path::new("/abc/def"), Path::new("/abc/ghi")),
+            "../ghi"
+        );
+        assert_eq!(shortest_cd(Path::new("/a/b/c"), Path::new("/d")), "/d");
+        assert_eq!(shortest_cd(Path::new("/a/b"), Path::new("/")), "/");
+    }
+
+    #[test]
+    fn test_random_tree() {
+        let root = Entry::random(42, 4, 4);
+        let expected: HashMap<PathBuf, u64> = root.directory_sizes().into_iter().collect();
+        assert!(expected.len() > 10);
+
+        for order in ORDERS {
+            let transcript = generate(&root, order);
+            assert_eq!(parsed_sizes(&transcript), expected, "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn test_disk() {
+        let root = std::env::temp_dir().join(format!("day07-transcript-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src/bin")).unwrap();
+        fs::create_dir_all(root.join("empty dir")).unwrap();
+        fs::create_dir_all(root.join("data-2022.12")).unwrap();
+        fs::write(root.join("Cargo.toml"), "x".repeat(120)).unwrap();
+        fs::write(root.join("src/lib.rs"), "x".repeat(3000)).unwrap();
+        fs::write(root.join("src/bin/main.rs"), "x".repeat(45)).unwrap();
+        fs::write(root.join("data-2022.12/input.tar.gz"), "x".repeat(777)).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.join("src"), root.join("src-link")).unwrap();
+
+        let entry = Entry::from_disk(&root).unwrap();
+        let mut expected = HashMap::new();
+        disk_sizes(&root, Path::new(""), &mut expected);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(expected[Path::new("/")], 3942);
+        for order in ORDERS {
+            let transcript = generate(&entry, order);
+            assert_eq!(parsed_sizes(&transcript), expected, "{:?}", order);
+        }
+    }
+}
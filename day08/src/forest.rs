@@ -0,0 +1,401 @@
+use std::io::BufRead;
+
+use anyhow::{bail, Result};
+
+/// 見る方向。木から見て、その方向の端まで見る。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Top,
+    Right,
+    Bottom,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Top,
+        Direction::Right,
+        Direction::Bottom,
+    ];
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// 斜めも含めた8方向。y は下向きに増える。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heading {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Heading {
+    pub const ALL: [Heading; 8] = [
+        Heading::North,
+        Heading::NorthEast,
+        Heading::East,
+        Heading::SouthEast,
+        Heading::South,
+        Heading::SouthWest,
+        Heading::West,
+        Heading::NorthWest,
+    ];
+
+    /// 1歩で進む (x, y)
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Heading::North => (0, -1),
+            Heading::NorthEast => (1, -1),
+            Heading::East => (1, 0),
+            Heading::SouthEast => (1, 1),
+            Heading::South => (0, 1),
+            Heading::SouthWest => (-1, 1),
+            Heading::West => (-1, 0),
+            Heading::NorthWest => (-1, -1),
+        }
+    }
+}
+
+impl From<Direction> for Heading {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Left => Heading::West,
+            Direction::Top => Heading::North,
+            Direction::Right => Heading::East,
+            Direction::Bottom => Heading::South,
+        }
+    }
+}
+
+/// 木ごとの結果の表。(x, y) は左上が (0, 0)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &T {
+        &self.cells[y * self.width + x]
+    }
+
+    /// 上の行から順に
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // 幅0のときも高さの数だけ行を返す
+        (0..self.height).map(move |y| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+}
+
+/// どの端から見えるか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Visibility(u8);
+
+impl Visibility {
+    pub fn from(&self, direction: Direction) -> bool {
+        self.0 & direction.bit() != 0
+    }
+
+    /// どこかの端から見える
+    pub fn any(&self) -> bool {
+        self.0 != 0
+    }
+}
+
+/// 木の高さの表
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forest {
+    width: usize,
+    height: usize,
+    heights: Vec<u8>,
+}
+
+impl Forest {
+    /// heights は上の行から順に並べたもの
+    pub fn new(width: usize, height: usize, heights: Vec<u8>) -> Result<Self> {
+        if heights.len() != width * height {
+            bail!(
+                "expected {}x{} trees but found {}",
+                width,
+                height,
+                heights.len()
+            );
+        }
+        Ok(Self {
+            width,
+            height,
+            heights,
+        })
+    }
+
+    /// 1行に1列ずつ、数字で高さを並べたもの。行の長さはそろっていること。
+    /// 空行は読み飛ばす。
+    pub fn parse(r: impl BufRead) -> Result<Self> {
+        let mut width = None;
+        let mut height = 0;
+        let mut heights = Vec::new();
+
+        for (y, line) in r.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            for (x, c) in line.chars().enumerate() {
+                let Some(h) = c.to_digit(10) else {
+                    bail!("line {}, column {}: not a digit {:?}", y + 1, x + 1, c);
+                };
+                heights.push(h as u8);
+            }
+
+            let len = line.chars().count();
+            if *width.get_or_insert(len) != len {
+                bail!(
+                    "line {}: expected {} trees but found {}",
+                    y + 1,
+                    width.unwrap(),
+                    len
+                );
+            }
+            height += 1;
+        }
+
+        Self::new(width.unwrap_or(0), height, heights)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.heights[y * self.width + x]
+    }
+
+    pub(crate) fn grid<T>(&self, cells: Vec<T>) -> Grid<T> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells,
+        }
+    }
+
+    /// 各木が、どの端から見えるか
+    pub fn visibility(&self) -> Grid<Visibility> {
+        let mut cells = vec![Visibility::default(); self.heights.len()];
+        for direction in Direction::ALL {
+            self.scan(direction.into(), |index, _, visible| {
+                if visible {
+                    cells[index].0 |= direction.bit();
+                }
+            });
+        }
+        self.grid(cells)
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.visibility().iter().filter(|v| v.any()).count()
+    }
+
+    /// 各木から、その方向に見える木の数。斜めの方向も数えられる。
+    pub fn view_distances(&self, heading: impl Into<Heading>) -> Grid<u32> {
+        let mut cells = vec![0; self.heights.len()];
+        self.scan(heading.into(), |index, distance, _| cells[index] = distance);
+        self.grid(cells)
+    }
+
+    /// 各木の4方向に見える木の数の積
+    pub fn scenic_scores(&self) -> Grid<u64> {
+        let mut cells = vec![1; self.heights.len()];
+        for direction in Direction::ALL {
+            self.scan(direction.into(), |index, distance, _| {
+                cells[index] *= distance as u64
+            });
+        }
+        self.grid(cells)
+    }
+
+    pub fn max_scenic_score(&self) -> u64 {
+        self.scenic_scores().iter().copied().max().unwrap_or(0)
+    }
+
+    /// 見る方向に並んだ木の列を、見る方向の端から順に走査する。
+    /// 単調スタックに高さが減っていく木を積み、低い木を取り除くと、残った先頭が視界を遮る木になる。
+    /// f(位置, 見える木の数, 端から見えるか) を各木で1回ずつ呼ぶ。
+    fn scan(&self, heading: Heading, mut f: impl FnMut(usize, u32, bool)) {
+        let (dx, dy) = heading.delta();
+        let (w, h) = (self.width as isize, self.height as isize);
+        let inside = |x: isize, y: isize| (0..w).contains(&x) && (0..h).contains(&y);
+
+        // (列の中の位置, 高さ)
+        let mut stack: Vec<(usize, u8)> = Vec::new();
+        // 見る方向の次が外になる木(見る方向の端の列と行)から、列が始まる
+        let (edge_x, edge_y) = (
+            if dx > 0 { w - 1 } else { 0 },
+            if dy > 0 { h - 1 } else { 0 },
+        );
+        let starts = (0..h).filter(|_| dx != 0).map(|y| (edge_x, y)).chain(
+            (0..w)
+                .filter(|&x| dy != 0 && (dx == 0 || x != edge_x))
+                .map(|x| (x, edge_y)),
+        );
+        for (x, y) in starts {
+            stack.clear();
+            let (mut x, mut y, mut pos) = (x, y, 0);
+            while inside(x, y) {
+                let i = (y * w + x) as usize;
+                let height = self.heights[i];
+                while stack.last().is_some_and(|&(_, h)| h < height) {
+                    stack.pop();
+                }
+                match stack.last() {
+                    Some(&(blocker, _)) => f(i, (pos - blocker) as u32, false),
+                    None => f(i, pos as u32, true),
+                }
+                stack.push((pos, height));
+                (x, y, pos) = (x - dx, y - dy, pos + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> Forest {
+        Forest::parse(include_str!("../data/sample.txt").as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let forest = sample();
+        assert_eq!((forest.width(), forest.height()), (5, 5));
+        assert_eq!(forest.get(0, 0), 3);
+        assert_eq!(forest.get(1, 2), 5);
+        assert_eq!(forest.get(4, 3), 9);
+
+        let e = Forest::parse("123\n1x3\n".as_bytes()).unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 2: not a digit 'x'");
+        let e = Forest::parse("123\n12\n".as_bytes()).unwrap_err();
+        assert_eq!(e.to_string(), "line 2: expected 3 trees but found 2");
+
+        // 空行は飛ばすが、行番号は入力のまま
+        let forest = Forest::parse("123\n\n456\n\n".as_bytes()).unwrap();
+        assert_eq!((forest.width(), forest.height()), (3, 2));
+        assert_eq!(forest.get(2, 1), 6);
+        let e = Forest::parse("123\n\n4\n".as_bytes()).unwrap_err();
+        assert_eq!(e.to_string(), "line 3: expected 3 trees but found 1");
+    }
+
+    #[test]
+    fn test_visibility() {
+        let forest = sample();
+        let visibility = forest.visibility();
+
+        let table: Vec<String> = visibility
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|v| if v.any() { 'o' } else { 'x' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(table, vec!["ooooo", "oooxo", "ooxoo", "oxoxo", "ooooo"]);
+        assert_eq!(forest.visible_count(), 21);
+
+        // 左上の5は上と左から見える
+        assert!(visibility.get(1, 1).from(Direction::Left));
+        assert!(visibility.get(1, 1).from(Direction::Top));
+        assert!(!visibility.get(1, 1).from(Direction::Right));
+    }
+
+    #[test]
+    fn test_scenic_scores() {
+        let forest = sample();
+
+        let distances: Vec<u32> = Direction::ALL
+            .iter()
+            .map(|&d| *forest.view_distances(d).get(2, 1))
+            .collect();
+        assert_eq!(distances, vec![1, 1, 2, 2]);
+        let distances: Vec<u32> = Direction::ALL
+            .iter()
+            .map(|&d| *forest.view_distances(d).get(2, 3))
+            .collect();
+        assert_eq!(distances, vec![2, 2, 2, 1]);
+
+        let scores = forest.scenic_scores();
+        assert_eq!(*scores.get(2, 1), 4);
+        assert_eq!(*scores.get(2, 3), 8);
+        assert_eq!(forest.max_scenic_score(), 8);
+    }
+
+    #[test]
+    fn test_non_square() {
+        let forest = Forest::parse("30373\n25512\n".as_bytes()).unwrap();
+        assert_eq!((forest.width(), forest.height()), (5, 2));
+        assert_eq!(forest.visible_count(), 10);
+        assert_eq!(forest.max_scenic_score(), 0);
+
+        let forest = Forest::parse("1\n5\n2\n".as_bytes()).unwrap();
+        assert_eq!(forest.visible_count(), 3);
+        assert_eq!(forest.view_distances(Direction::Bottom).get(0, 0), &1);
+
+        let forest = Forest::parse("".as_bytes()).unwrap();
+        assert_eq!(forest.visible_count(), 0);
+        assert_eq!(forest.max_scenic_score(), 0);
+    }
+
+    /// 端からの距離を高さにした段々
+    fn check_staircase(width: usize, height: usize) {
+        let heights = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                let edge = x.min(y).min(width - 1 - x).min(height - 1 - y);
+                (edge % 10) as u8
+            })
+            .collect();
+        let forest = Forest::new(width, height, heights).unwrap();
+
+        let visibility = forest.visibility();
+        assert!(visibility.get(0, 0).any());
+        assert!(visibility.get(9, 9).any());
+        assert!(!visibility.get(10, 10).any());
+        assert_eq!(*forest.scenic_scores().get(1, 1), 1);
+    }
+
+    #[test]
+    fn test_large() {
+        check_staircase(5000, 400);
+    }
+
+    #[test]
+    #[ignore = "時間がかかるので release ビルドで --ignored を付けて実行する"]
+    fn test_huge() {
+        check_staircase(5000, 5000);
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use crate::forest::Grid;
+
+impl<T: Display> Grid<T> {
+    /// 1行を1行に、カンマ区切りで書き出す
+    pub fn write_csv(&self, mut w: impl Write) -> io::Result<()> {
+        for row in self.rows() {
+            let line: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+            writeln!(w, "{}", line.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Copy + Into<u64>> Grid<T> {
+    /// 1マス1ピクセルの PPM(P6) 画像として書き出す。
+    /// 最大値で割って、黒→赤→黄→白 の順に明るくなる色にする。
+    pub fn write_ppm(&self, mut w: impl Write) -> io::Result<()> {
+        let max = self.iter().map(|&v| v.into()).max().unwrap_or(0);
+        write!(w, "P6\n{} {}\n255\n", self.width(), self.height())?;
+
+        let mut pixels = Vec::with_capacity(self.width() * self.height() * 3);
+        for &v in self.iter() {
+            let t = if max == 0 {
+                0.0
+            } else {
+                v.into() as f64 / max as f64
+            };
+            pixels.extend(heat(t));
+        }
+        w.write_all(&pixels)
+    }
+}
+
+/// 0.0 から 1.0 の値の色
+fn heat(t: f64) -> [u8; 3] {
+    let channel = |offset: f64| ((3.0 * t - offset).clamp(0.0, 1.0) * 255.0).round() as u8;
+    [channel(0.0), channel(1.0), channel(2.0)]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Forest;
+
+    use super::*;
+
+    fn sample() -> Forest {
+        Forest::parse(include_str!("../data/sample.txt").as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut csv = Vec::new();
+        sample().scenic_scores().write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "0,0,0,0,0\n0,1,4,1,0\n0,6,1,2,0\n0,1,8,3,0\n0,0,0,0,0\n"
+        );
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut ppm = Vec::new();
+        sample().scenic_scores().write_ppm(&mut ppm).unwrap();
+
+        let header = b"P6\n5 5\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        let pixels = &ppm[header.len()..];
+        assert_eq!(pixels.len(), 5 * 5 * 3);
+        // 0 は黒、最大の 8 は白、4 は赤と黄の間
+        assert_eq!(pixels[0..3], [0, 0, 0]);
+        assert_eq!(pixels[(3 * 5 + 2) * 3..][..3], [255, 255, 255]);
+        assert_eq!(pixels[(5 + 2) * 3..][..3], [255, 128, 0]);
+
+        assert_eq!(heat(0.0), [0, 0, 0]);
+        assert_eq!(heat(1.0 / 3.0), [255, 0, 0]);
+    }
+}
@@ -1 +1,6 @@
+pub use crate::forest::{Direction, Forest, Grid, Heading, Visibility};
+pub use crate::viewpoint::Observer;
 
+mod forest;
+mod heatmap;
+mod viewpoint;
@@ -0,0 +1,269 @@
+use std::cmp::Ordering;
+
+use anyhow::{bail, Result};
+
+use crate::forest::{Forest, Grid, Heading};
+
+/// 森の外から見る人。(x, y) は木と同じ座標で、森の外側の位置。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observer {
+    pub x: isize,
+    pub y: isize,
+    /// 目の高さ。木の高さと同じ単位。
+    pub height: f64,
+}
+
+impl Forest {
+    /// (x, y) の木から heading の方向に見える木の位置。近い順。
+    /// 同じ高さか高い木で視界が遮られ、その木までが見える。
+    pub fn view_from(
+        &self,
+        x: usize,
+        y: usize,
+        heading: impl Into<Heading>,
+    ) -> Vec<(usize, usize)> {
+        let (dx, dy) = heading.into().delta();
+        let height = self.get(x, y);
+        let mut trees = Vec::new();
+        let (mut x, mut y) = (x as isize + dx, y as isize + dy);
+        while let Some((tx, ty)) = self.position(x, y) {
+            trees.push((tx, ty));
+            if self.get(tx, ty) >= height {
+                break;
+            }
+            (x, y) = (x + dx, y + dy);
+        }
+        trees
+    }
+
+    /// (x, y) の木から斜めも含めた8方向に見える木の位置。上の行から順に並べる。
+    pub fn visible_from(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut trees: Vec<(usize, usize)> = Heading::ALL
+            .iter()
+            .flat_map(|&heading| self.view_from(x, y, heading))
+            .collect();
+        trees.sort_by_key(|&(x, y)| (y, x));
+        trees
+    }
+
+    /// 森の外の observer から見える木。
+    ///
+    /// 目から木のてっぺんへの傾き (高さの差 / 距離) が、間にあるどの木の傾きより
+    /// 大きければ見える。間にある木は、目と木の中心を結ぶ線分が通るマスの木。
+    ///
+    /// 傾きは先に1回ずつ求めておき、木ごとに線分をたどるので、
+    /// 森の近くの視点なら O(w·h·(w+h))。
+    pub fn line_of_sight(&self, observer: Observer) -> Result<Grid<bool>> {
+        let Observer {
+            x: ox,
+            y: oy,
+            height: eye,
+        } = observer;
+        if self.position(ox, oy).is_some() {
+            bail!("observer at ({}, {}) is inside the forest", ox, oy);
+        }
+        let mut slopes = Vec::with_capacity(self.width() * self.height());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let distance = ((x as isize - ox) as f64).hypot((y as isize - oy) as f64);
+                slopes.push((self.get(x, y) as f64 - eye) / distance);
+            }
+        }
+        let slopes = self.grid(slopes);
+
+        let mut cells = Vec::with_capacity(self.width() * self.height());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let target = *slopes.get(x, y);
+                let visible = cells_between((ox, oy), (x as isize, y as isize))
+                    // 森に入る前のマスは飛ばす
+                    .skip_while(|&(cx, cy)| self.position(cx, cy).is_none())
+                    .map_while(|(cx, cy)| self.position(cx, cy))
+                    .all(|(cx, cy)| *slopes.get(cx, cy) < target);
+                cells.push(visible);
+            }
+        }
+        Ok(self.grid(cells))
+    }
+
+    fn position(&self, x: isize, y: isize) -> Option<(usize, usize)> {
+        let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+        (x < self.width() && y < self.height()).then_some((x, y))
+    }
+}
+
+/// from と to の中心を結ぶ線分が通るマス。両端は含まない。
+/// 線分がマスの角をちょうど通るときは、斜めに1歩で進む。
+fn cells_between(from: (isize, isize), to: (isize, isize)) -> impl Iterator<Item = (isize, isize)> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let (nx, ny) = (dx.abs(), dy.abs());
+    let (sx, sy) = (dx.signum(), dy.signum());
+
+    let (mut x, mut y) = from;
+    let (mut ix, mut iy) = (0, 0);
+    std::iter::from_fn(move || {
+        // 次に横の境界と縦の境界のどちらを先に越えるか
+        match ((1 + 2 * ix) * ny).cmp(&((1 + 2 * iy) * nx)) {
+            Ordering::Equal => {
+                (x, y, ix, iy) = (x + sx, y + sy, ix + 1, iy + 1);
+            }
+            Ordering::Less => (x, ix) = (x + sx, ix + 1),
+            Ordering::Greater => (y, iy) = (y + sy, iy + 1),
+        }
+        // 最後のマスは to なので含めない
+        (ix < nx || iy < ny).then_some((x, y))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> Forest {
+        Forest::parse(include_str!("../data/sample.txt").as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_view_from() {
+        let forest = sample();
+
+        // 中央下の5から
+        assert_eq!(forest.view_from(2, 3, Heading::North), vec![(2, 2), (2, 1)]);
+        assert_eq!(
+            forest.view_from(2, 3, Heading::NorthEast),
+            vec![(3, 2), (4, 1)]
+        );
+        assert_eq!(forest.view_from(2, 3, Heading::SouthWest), vec![(1, 4)]);
+        assert_eq!(forest.view_from(0, 0, Heading::NorthWest), vec![]);
+
+        let trees = forest.visible_from(2, 3);
+        assert_eq!(trees.len(), 12);
+        assert_eq!(trees[0], (2, 1));
+        assert_eq!(trees.last(), Some(&(3, 4)));
+    }
+
+    #[test]
+    fn test_view_distances() {
+        // 1本ずつたどった結果と、まとめて求めた結果が同じ
+        let heights = (0..13 * 7).map(|i| ((i * 7 + i / 5) % 10) as u8).collect();
+        let forest = Forest::new(13, 7, heights).unwrap();
+        for forest in [sample(), forest] {
+            for heading in Heading::ALL {
+                let distances = forest.view_distances(heading);
+                for y in 0..forest.height() {
+                    for x in 0..forest.width() {
+                        assert_eq!(
+                            *distances.get(x, y) as usize,
+                            forest.view_from(x, y, heading).len(),
+                            "({}, {}) {:?}",
+                            x,
+                            y,
+                            heading
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cells_between() {
+        let cells = |from, to| cells_between(from, to).collect::<Vec<_>>();
+        assert_eq!(cells((0, 0), (3, 0)), vec![(1, 0), (2, 0)]);
+        assert_eq!(cells((0, 0), (2, 2)), vec![(1, 1)]);
+        assert_eq!(cells((0, 0), (2, 1)), vec![(1, 0), (1, 1)]);
+        assert_eq!(cells((-1, 2), (0, 2)), vec![]);
+        assert_eq!(cells((3, 3), (3, 3)), vec![]);
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let forest = Forest::parse("35123\n".as_bytes()).unwrap();
+        let row = |eye: f64| -> Vec<bool> {
+            let observer = Observer {
+                x: -1,
+                y: 0,
+                height: eye,
+            };
+            forest
+                .line_of_sight(observer)
+                .unwrap()
+                .iter()
+                .copied()
+                .collect()
+        };
+
+        // 傾きは 3, 2.5, 1/3, 0.5, 0.6
+        assert_eq!(row(0.0), vec![true, false, false, false, false]);
+        // 傾きは -7, -2.5, -3, -2, -1.4
+        assert_eq!(row(10.0), vec![true, true, false, true, true]);
+        // 十分高ければ全部見える
+        assert!(row(1000.0).iter().all(|&v| v));
+
+        let forest = sample();
+        let observer = Observer {
+            x: -3,
+            y: -3,
+            height: 4.5,
+        };
+        let visible = forest.line_of_sight(observer).unwrap();
+        assert!(*visible.get(0, 0));
+        assert!(*visible.get(1, 1));
+        // 手前の5に隠れる
+        assert!(!*visible.get(2, 2));
+
+        let inside = Observer {
+            x: 2,
+            y: 2,
+            height: 0.0,
+        };
+        assert!(forest.line_of_sight(inside).is_err());
+    }
+
+    #[test]
+    fn test_line_of_sight_large() {
+        let (width, height) = (120, 90);
+        let heights = (0..width * height)
+            .map(|i| ((i * 7 + i / 11 + i / 97) % 10) as u8)
+            .collect();
+        let forest = Forest::new(width, height, heights).unwrap();
+
+        // 行・列・対角線に並んだ木だけで、端から順に傾きを比べた結果と同じ
+        let expected = |line: &[(usize, usize)], eye: f64, step: f64| -> Vec<bool> {
+            let mut highest = f64::NEG_INFINITY;
+            line.iter()
+                .enumerate()
+                .map(|(i, &(x, y))| {
+                    let slope = (forest.get(x, y) as f64 - eye) / ((i + 1) as f64 * step);
+                    let visible = highest < slope;
+                    highest = highest.max(slope);
+                    visible
+                })
+                .collect()
+        };
+        for eye in [0.0, 4.5, 9.0] {
+            let row: Vec<_> = (0..width).map(|x| (x, 40)).collect();
+            let column: Vec<_> = (0..height).map(|y| (70, y)).collect();
+            let diagonal: Vec<_> = (0..height).map(|i| (i, i)).collect();
+            for (line, x, y, step) in [
+                (row, -1, 40, 1.0),
+                (column, 70, -1, 1.0),
+                (diagonal, -1, -1, 2f64.sqrt()),
+            ] {
+                let observer = Observer { x, y, height: eye };
+                let visible = forest.line_of_sight(observer).unwrap();
+                let actual: Vec<bool> = line.iter().map(|&(x, y)| *visible.get(x, y)).collect();
+                assert_eq!(actual, expected(&line, eye, step), "{:?}", observer);
+            }
+        }
+
+        // 十分高ければ遠くからでも全部見える
+        let observer = Observer {
+            x: -500,
+            y: 300,
+            height: 1e6,
+        };
+        let visible = forest.line_of_sight(observer).unwrap();
+        assert!(visible.iter().all(|&v| v));
+    }
+}
@@ -1,13 +1,15 @@
 use std::fs::File;
 use std::io::BufReader;
 
-use day09::{simulate_tail, Rope};
+use day09::{parse_motions, visited_cells};
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day09/data/input.txt")?);
+    let path = input_path("day09");
+    let r = BufReader::new(File::open(path)?);
 
-    let mut rope = Rope::new(10);
-    let tails = simulate_tail(r, &mut rope);
+    let motions = parse_motions(r)?;
+    let tails = visited_cells(10, motions, 9);
     println!("answer: {}", tails.len());
 
     Ok(())
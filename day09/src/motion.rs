@@ -0,0 +1,102 @@
+use std::io::BufRead;
+
+use anyhow::{anyhow, Result};
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{digit1, space1};
+use nom::combinator::{all_consuming, map, value};
+use nom::IResult;
+
+/// Hを動かす方向。斜めにも動かせる。
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+    Left,
+    Up,
+    Right,
+    Down,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    /// 1歩で進む (x, y)。上が y の正の向き。
+    pub fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Up => (0, 1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, -1),
+            Direction::UpLeft => (-1, 1),
+            Direction::UpRight => (1, 1),
+            Direction::DownLeft => (-1, -1),
+            Direction::DownRight => (1, -1),
+        }
+    }
+}
+
+/// 命令文字列をパース
+/// "R 3" => (Direction::Right, 3)
+/// "UL 2" => (Direction::UpLeft, 2)
+pub(crate) fn parse_direction(input: &str) -> IResult<&str, (Direction, u32)> {
+    let (input, direction) = alt((
+        value(Direction::UpLeft, tag("UL")),
+        value(Direction::UpRight, tag("UR")),
+        value(Direction::DownLeft, tag("DL")),
+        value(Direction::DownRight, tag("DR")),
+        value(Direction::Left, tag("L")),
+        value(Direction::Up, tag("U")),
+        value(Direction::Right, tag("R")),
+        value(Direction::Down, tag("D")),
+    ))(input)?;
+
+    let (input, _) = space1(input)?;
+    let (input, value) = map(digit1, |s: &str| s.parse::<u32>().unwrap())(input)?;
+
+    Ok((input, (direction, value)))
+}
+
+/// 1行に1つの命令を読む。空行は読み飛ばす。
+pub fn parse_motions(r: impl BufRead) -> Result<Vec<(Direction, u32)>> {
+    let mut motions = Vec::new();
+    for (i, line) in r.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        let (_, motion) = all_consuming(parse_direction)(line)
+            .map_err(|_| anyhow!("line {}: invalid motion {:?}", i + 1, line))?;
+        motions.push(motion);
+    }
+    Ok(motions)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_direction() {
+        assert_eq!(parse_direction("L 1"), Ok(("", (Direction::Left, 1))));
+        assert_eq!(parse_direction("U 2"), Ok(("", (Direction::Up, 2))));
+        assert_eq!(parse_direction("R 3"), Ok(("", (Direction::Right, 3))));
+        assert_eq!(parse_direction("D 4"), Ok(("", (Direction::Down, 4))));
+        assert_eq!(parse_direction("UL 5"), Ok(("", (Direction::UpLeft, 5))));
+        assert_eq!(parse_direction("DR 6"), Ok(("", (Direction::DownRight, 6))));
+    }
+
+    #[test]
+    fn test_parse_motions() {
+        let motions = parse_motions("R 4\nUR 2\n\n".as_bytes()).unwrap();
+        assert_eq!(
+            motions,
+            vec![(Direction::Right, 4), (Direction::UpRight, 2)]
+        );
+
+        let e = parse_motions("R 4\nX 2\n".as_bytes()).unwrap_err();
+        assert_eq!(e.to_string(), "line 2: invalid motion \"X 2\"");
+        assert!(parse_motions("R 4x\n".as_bytes()).is_err());
+    }
+}
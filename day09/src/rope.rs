@@ -0,0 +1,279 @@
+use std::collections::HashSet;
+
+use crate::motion::Direction;
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// 距離がXYともに1以内なら隣接している(重なっていてもよい)
+    pub fn is_touching(&self, other: &Point) -> bool {
+        (self.x - other.x).abs() <= 1 && (self.y - other.y).abs() <= 1
+    }
+
+    /// lead に引っ張られた後の位置。
+    /// 離れていれば、XYそれぞれ lead の方へ1歩ずつ(斜めにも)近づく。
+    pub fn follow(self, lead: Point) -> Point {
+        if self.is_touching(&lead) {
+            return self;
+        }
+        Point {
+            x: self.x + (lead.x - self.x).signum(),
+            y: self.y + (lead.y - self.y).signum(),
+        }
+    }
+}
+
+/// 結び目の並び。先頭がH、最後が尾。
+#[derive(Debug, Clone)]
+pub struct Rope {
+    knots: Vec<Point>,
+}
+
+impl Rope {
+    /// すべての結び目が原点にある
+    pub fn new(len: usize) -> Rope {
+        assert!(len >= 2);
+
+        Rope {
+            knots: vec![Point::default(); len],
+        }
+    }
+
+    pub fn knots(&self) -> &[Point] {
+        &self.knots
+    }
+
+    pub fn head(&self) -> Point {
+        self.knots[0]
+    }
+
+    pub fn tail(&self) -> Point {
+        *self.knots.last().unwrap()
+    }
+
+    /// 指定方向へHを1歩移動、結び目はついてくる。
+    pub fn step(&mut self, direction: Direction) {
+        let (dx, dy) = direction.delta();
+        self.knots[0].x += dx;
+        self.knots[0].y += dy;
+
+        for i in 1..self.knots.len() {
+            let next = self.knots[i].follow(self.knots[i - 1]);
+            // 動かなければ、その先の結び目も動かない
+            if next == self.knots[i] {
+                break;
+            }
+            self.knots[i] = next;
+        }
+    }
+
+    /// 命令を1歩ずつ実行し、各歩の後の結び目の位置を返すイテレータ
+    pub fn frames<I>(self, motions: I) -> Frames<I::IntoIter>
+    where
+        I: IntoIterator<Item = (Direction, u32)>,
+    {
+        Frames {
+            rope: self,
+            motions: motions.into_iter(),
+            current: None,
+            step: 0,
+        }
+    }
+}
+
+/// ある歩の後の結び目の位置。step は1から数える。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub step: usize,
+    pub knots: Vec<Point>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Frames<I> {
+    rope: Rope,
+    motions: I,
+    // (方向, 残りの歩数)
+    current: Option<(Direction, u32)>,
+    step: usize,
+}
+
+impl<I> Frames<I> {
+    /// ここまで進めたロープ
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+}
+
+impl<I: Iterator<Item = (Direction, u32)>> Iterator for Frames<I> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        let direction = loop {
+            match self.current {
+                Some((direction, remaining)) if remaining > 0 => {
+                    self.current = Some((direction, remaining - 1));
+                    break direction;
+                }
+                _ => self.current = Some(self.motions.next()?),
+            }
+        };
+
+        self.rope.step(direction);
+        self.step += 1;
+        Some(Frame {
+            step: self.step,
+            knots: self.rope.knots.clone(),
+        })
+    }
+}
+
+/// knot 番目の結び目が通ったマス。最初の位置(原点)も含む。
+pub fn visited_cells(
+    len: usize,
+    motions: impl IntoIterator<Item = (Direction, u32)>,
+    knot: usize,
+) -> HashSet<Point> {
+    assert!(knot < len);
+    let mut cells = HashSet::from([Point::default()]);
+    cells.extend(
+        Rope::new(len)
+            .frames(motions)
+            .map(|frame| frame.knots[knot]),
+    );
+    cells
+}
+
+/// すべての結び目の通ったマスの数。先頭がH。
+pub fn visited_counts(
+    len: usize,
+    motions: impl IntoIterator<Item = (Direction, u32)>,
+) -> Vec<usize> {
+    let mut cells = vec![HashSet::from([Point::default()]); len];
+    for frame in Rope::new(len).frames(motions) {
+        for (visited, knot) in cells.iter_mut().zip(frame.knots) {
+            visited.insert(knot);
+        }
+    }
+    cells.iter().map(|visited| visited.len()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::motion::parse_motions;
+
+    fn motions(text: &str) -> Vec<(Direction, u32)> {
+        parse_motions(text.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_follow() {
+        assert_eq!(Point::new(0, 0).follow(Point::new(1, 2)), Point::new(1, 1));
+        assert_eq!(Point::new(0, 0).follow(Point::new(2, 1)), Point::new(1, 1));
+        assert_eq!(Point::new(0, 0).follow(Point::new(2, 0)), Point::new(1, 0));
+        // 斜めに2つ離れたとき(長いロープで起きる)は斜めに動く
+        assert_eq!(Point::new(0, 0).follow(Point::new(2, 2)), Point::new(1, 1));
+        assert_eq!(Point::new(0, 0).follow(Point::new(1, -1)), Point::new(0, 0));
+    }
+
+    #[test]
+    fn test_is_touching() {
+        let origin = Point::new(0, 0);
+        for (x, y) in [
+            (0, 0),
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, -1),
+            (-1, 1),
+        ] {
+            assert!(origin.is_touching(&Point::new(x, y)));
+        }
+        assert!(!origin.is_touching(&Point::new(2, 0)));
+        assert!(!origin.is_touching(&Point::new(2, 1)));
+    }
+
+    #[test]
+    fn test_step() {
+        let mut rope = Rope::new(2);
+        rope.step(Direction::Right);
+        rope.step(Direction::Right);
+        assert_eq!(rope.knots(), [Point::new(2, 0), Point::new(1, 0)]);
+
+        let mut rope = Rope::new(2);
+        rope.step(Direction::Down);
+        rope.step(Direction::Down);
+        assert_eq!(rope.knots(), [Point::new(0, -2), Point::new(0, -1)]);
+
+        let mut rope = Rope::new(2);
+        rope.step(Direction::Right);
+        rope.step(Direction::Up);
+        rope.step(Direction::Up);
+        assert_eq!(rope.knots(), [Point::new(1, 2), Point::new(1, 1)]);
+
+        let mut rope = Rope::new(2);
+        rope.step(Direction::Right);
+        rope.step(Direction::Up);
+        rope.step(Direction::Right);
+        assert_eq!(rope.knots(), [Point::new(2, 1), Point::new(1, 1)]);
+
+        // 斜めの移動
+        let mut rope = Rope::new(3);
+        rope.step(Direction::UpRight);
+        assert_eq!(rope.tail(), Point::new(0, 0));
+        rope.step(Direction::UpRight);
+        assert_eq!(
+            rope.knots(),
+            [Point::new(2, 2), Point::new(1, 1), Point::new(0, 0)]
+        );
+        rope.step(Direction::DownRight);
+        assert_eq!(
+            rope.knots(),
+            [Point::new(3, 1), Point::new(2, 1), Point::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_frames() {
+        let frames: Vec<Frame> = Rope::new(3).frames(motions("R 2\nU 1\n")).collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(
+            frames[1],
+            Frame {
+                step: 2,
+                knots: vec![Point::new(2, 0), Point::new(1, 0), Point::new(0, 0)],
+            }
+        );
+        assert_eq!(frames[2].knots[0], Point::new(2, 1));
+
+        // 0歩の命令は飛ばす
+        let mut frames = Rope::new(2).frames(motions("L 0\nL 1\n"));
+        assert_eq!(frames.next().unwrap().knots[0], Point::new(-1, 0));
+        assert_eq!(frames.next(), None);
+        assert_eq!(frames.rope().head(), Point::new(-1, 0));
+    }
+
+    #[test]
+    fn test_visited() {
+        let sample = motions(include_str!("../data/sample.txt"));
+        assert_eq!(visited_cells(2, sample.clone(), 1).len(), 13);
+        let counts = visited_counts(10, sample);
+        assert_eq!(counts[1], 13);
+        assert_eq!(counts[9], 1);
+
+        let sample2 = motions(include_str!("../data/sample2.txt"));
+        assert_eq!(visited_counts(10, sample2.clone())[9], 36);
+        assert_eq!(visited_cells(10, sample2, 9).len(), 36);
+    }
+}
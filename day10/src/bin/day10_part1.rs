@@ -6,9 +6,11 @@ use nom::sequence::{preceded, tuple};
 use nom::IResult;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day10/data/input.txt")?);
+    let path = input_path("day10");
+    let r = BufReader::new(File::open(path)?);
     let signals = simulate(r);
 
     let sum: i32 = signals
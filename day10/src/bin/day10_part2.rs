@@ -7,9 +7,11 @@ use nom::character::complete::digit1;
 use nom::combinator::{map, opt, recognize, value};
 use nom::IResult;
 use nom::sequence::{preceded, tuple};
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day10/data/input.txt")?);
+    let path = input_path("day10");
+    let r = BufReader::new(File::open(path)?);
 
     let mut w = BufWriter::new(std::io::stdout());
     draw(&mut w, r);
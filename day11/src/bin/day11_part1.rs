@@ -9,9 +9,11 @@ use nom::combinator::{map, value};
 use nom::multi::separated_list1;
 use nom::sequence::{delimited, preceded};
 use nom::IResult;
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let text = read_to_string("./day11/data/input.txt")?;
+    let path = input_path("day11");
+    let text = read_to_string(path)?;
     let (_, mut monkeys) = parse_monkeys(&text).unwrap();
 
     let mut inspect_counts = vec![0; monkeys.len()];
@@ -10,28 +10,140 @@ use nom::multi::separated_list1;
 use nom::sequence::{delimited, preceded};
 use nom::IResult;
 
+use budget::{Budget, Checkpoint, Solve, Stats};
+use tools::input::input_path;
+
 fn main() -> anyhow::Result<()> {
-    let text = read_to_string("./day11/data/input.txt")?;
-    let (_, mut monkeys) = parse_monkeys(&text).unwrap();
+    let path = input_path("day11");
+    let text = read_to_string(path)?;
+    let (_, monkeys) = parse_monkeys(&text).unwrap();
+
+    let checkpoint = Checkpoint::from_env("day11_part2", 1000);
+    let budget = Budget::from_env();
+
+    let mut simulation = Simulation::new(monkeys);
+    match simulation.run(10000, &budget, checkpoint.as_ref())? {
+        Solve::Done { answer, .. } => println!("answer: {}", answer),
+        Solve::TimedOut { stats, .. } => println!("timed out: rounds: {}", stats.nodes),
+    }
 
+    Ok(())
+}
+
+/// ラウンドを進めるシミュレーション
+/// 途中経過をチェックポイントに保存して、再開できる。
+struct Simulation {
+    monkeys: Vec<Monkey>,
+    inspect_counts: Vec<u64>,
+    round: u32,
     // 最小公倍数
-    let k = get_lcm(&monkeys);
-
-    let mut inspect_counts = vec![0; monkeys.len()];
-    for i in 0..10000 {
-        do_round(&mut monkeys, &mut inspect_counts, k);
-        if matches!(
-            i + 1,
-            1 | 20 | 1000 | 2000 | 3000 | 4000 | 5000 | 6000 | 7000 | 8000 | 9000 | 10000
-        ) {
-            println!("count:{} inspect_counts:{:?}", i + 1, inspect_counts);
+    k: u32,
+}
+
+impl Simulation {
+    fn new(monkeys: Vec<Monkey>) -> Self {
+        let k = get_lcm(&monkeys);
+        let inspect_counts = vec![0; monkeys.len()];
+        Self {
+            monkeys,
+            inspect_counts,
+            round: 0,
+            k,
         }
     }
 
-    let monkey_business: u64 = inspect_counts.iter().sorted().rev().take(2).product();
-    println!("answer: {}", monkey_business);
+    /// 指定ラウンドまで進めて、monkey businessを返す。
+    fn run(
+        &mut self,
+        rounds: u32,
+        budget: &Budget,
+        checkpoint: Option<&Checkpoint>,
+    ) -> anyhow::Result<Solve<u64>> {
+        if let Some(text) = checkpoint.map(|c| c.load()).transpose()?.flatten() {
+            self.restore(&text)?;
+        }
 
-    Ok(())
+        let mut stats = Stats::resume(self.round as u64);
+        while self.round < rounds {
+            if let Some(checkpoint) = checkpoint {
+                if checkpoint.is_due(stats.nodes) {
+                    checkpoint.save(&self.to_checkpoint())?;
+                }
+            }
+            if stats.tick(budget) {
+                if let Some(checkpoint) = checkpoint {
+                    checkpoint.save(&self.to_checkpoint())?;
+                }
+                return Ok(Solve::TimedOut { best: None, stats });
+            }
+
+            do_round(&mut self.monkeys, &mut self.inspect_counts, self.k);
+            self.round += 1;
+
+            if matches!(
+                self.round,
+                1 | 20 | 1000 | 2000 | 3000 | 4000 | 5000 | 6000 | 7000 | 8000 | 9000 | 10000
+            ) {
+                println!(
+                    "count:{} inspect_counts:{:?}",
+                    self.round, self.inspect_counts
+                );
+            }
+        }
+
+        if let Some(checkpoint) = checkpoint {
+            checkpoint.clear()?;
+        }
+
+        let monkey_business: u64 = self.inspect_counts.iter().sorted().rev().take(2).product();
+        Ok(Solve::Done {
+            answer: monkey_business,
+            stats,
+        })
+    }
+
+    /// round 20
+    /// counts 101 95 7 105
+    /// items 0 10 81
+    /// items 1
+    fn to_checkpoint(&self) -> String {
+        let mut lines = vec![
+            format!("round {}", self.round),
+            format!("counts {}", self.inspect_counts.iter().join(" ")),
+        ];
+        for monkey in &self.monkeys {
+            let items = monkey.items.iter().map(|item| format!(" {}", item));
+            lines.push(format!("items {}{}", monkey.id, items.collect::<String>()));
+        }
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    fn restore(&mut self, text: &str) -> anyhow::Result<()> {
+        let illegal = || anyhow::anyhow!("illegal checkpoint: {:?}", text);
+
+        for line in text.lines() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("round") => {
+                    self.round = words.next().ok_or_else(illegal)?.parse()?;
+                }
+                Some("counts") => {
+                    self.inspect_counts = words.map(|w| w.parse()).collect::<Result<_, _>>()?;
+                }
+                Some("items") => {
+                    let id: usize = words.next().ok_or_else(illegal)?.parse()?;
+                    let monkey = self.monkeys.get_mut(id).ok_or_else(illegal)?;
+                    monkey.items = words.map(|w| w.parse()).collect::<Result<_, _>>()?;
+                }
+                _ => return Err(illegal()),
+            }
+        }
+
+        if self.inspect_counts.len() != self.monkeys.len() {
+            return Err(illegal());
+        }
+        Ok(())
+    }
 }
 
 fn do_round(monkeys: &mut [Monkey], inspect_counts: &mut [u64], k: u32) {
@@ -214,6 +326,64 @@ mod tests {
         assert_eq!(monkey_business, 2713310158);
     }
 
+    #[test]
+    fn test_resume() {
+        let text = include_str!("../../data/sample.txt");
+        let (_, monkeys) = parse_monkeys(text).unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("day11_test_{}.checkpoint", std::process::id()));
+        let checkpoint = Checkpoint::new(&path, 1000);
+
+        // 2500ラウンドで中断
+        let mut simulation = Simulation::new(monkeys);
+        let solve = simulation
+            .run(10000, &Budget::with_max_nodes(2500), Some(&checkpoint))
+            .unwrap();
+        assert!(matches!(solve, Solve::TimedOut { .. }));
+
+        // 新しいシミュレーションで再開して、同じ答えになること
+        let (_, monkeys) = parse_monkeys(text).unwrap();
+        let mut simulation = Simulation::new(monkeys);
+        simulation
+            .restore(&checkpoint.load().unwrap().unwrap())
+            .unwrap();
+        assert_eq!(simulation.round, 2500);
+
+        let (_, monkeys) = parse_monkeys(text).unwrap();
+        let mut simulation = Simulation::new(monkeys);
+        let solve = simulation
+            .run(10000, &Budget::unlimited(), Some(&checkpoint))
+            .unwrap();
+        assert_eq!(solve.answer(), Some(&2713310158));
+        assert_eq!(solve.stats().nodes, 10000);
+        assert_eq!(checkpoint.load().unwrap(), None);
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let text = include_str!("../../data/sample.txt");
+        let (_, monkeys) = parse_monkeys(text).unwrap();
+
+        let mut simulation = Simulation::new(monkeys);
+        simulation.run(20, &Budget::unlimited(), None).unwrap();
+
+        let checkpoint = simulation.to_checkpoint();
+        assert_eq!(
+            checkpoint.lines().take(2).collect::<Vec<_>>(),
+            vec!["round 20", "counts 99 97 8 103"]
+        );
+
+        let (_, monkeys) = parse_monkeys(text).unwrap();
+        let mut restored = Simulation::new(monkeys);
+        restored.restore(&checkpoint).unwrap();
+        assert_eq!(restored.monkeys, simulation.monkeys);
+        assert_eq!(restored.inspect_counts, simulation.inspect_counts);
+
+        assert!(restored.restore("round x").is_err());
+        assert!(restored.restore("unknown 1").is_err());
+    }
+
     #[test]
     fn test_parse_monkeys() {
         let text = include_str!("../../data/sample.txt");
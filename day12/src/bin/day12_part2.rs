@@ -1,11 +1,16 @@
 use std::fs::File;
 use std::io::BufReader;
 
-use day12::simulate;
+use day12::search;
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day12/data/input.txt")?);
-    let path = simulate(r, true).expect("Failed to simulate");
+    let path = input_path("day12");
+    let r = BufReader::new(File::open(path)?);
+    let finder = search(r, true);
+    #[cfg(feature = "render")]
+    finder.display_path();
+    let path = finder.path().expect("Failed to simulate");
     println!("answer: {}", path.len() - 1);
     Ok(())
 }
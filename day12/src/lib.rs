@@ -1,21 +1,21 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::io::BufRead;
 
+#[cfg(feature = "render")]
 use termion::{color, style};
 
+/// 最短経路を返す。表示はしない。
 pub fn simulate(r: impl BufRead, start_edge: bool) -> Option<Vec<Pos>> {
+    search(r, start_edge).path().cloned()
+}
+
+/// 探索を終えたFinderを返す。経路の表示はこちらから行う。
+pub fn search(r: impl BufRead, start_edge: bool) -> Finder {
     let mut finder = Finder::with_reader(r, start_edge);
 
     while finder.do_round() {}
 
-    let state = &finder.states[&finder.end_pos];
-    match state {
-        State::Certain(path) => {
-            finder.display_path();
-            Some(path.clone())
-        }
-        _ => None,
-    }
+    finder
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
@@ -39,7 +39,7 @@ fn to_u32(c: char) -> u32 {
     }
 }
 
-struct Finder {
+pub struct Finder {
     data: Vec<Vec<char>>,
     len_x: u32,
     len_y: u32,
@@ -203,7 +203,18 @@ impl Finder {
         true
     }
 
-    fn display_path(&self) {
+    /// ゴールまでの最短経路
+    pub fn path(&self) -> Option<&Vec<Pos>> {
+        match &self.states[&self.end_pos] {
+            State::Certain(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "render")]
+    pub fn display_path(&self) {
+        use std::collections::HashSet;
+
         let State::Certain(path) = &self.states[&self.end_pos] else {
             return;
         };
@@ -259,6 +270,13 @@ mod tests {
         assert_eq!(path.len() - 1, 29);
     }
 
+    #[test]
+    fn test_search() {
+        let finder = search(include_str!("../data/sample.txt").as_bytes(), false);
+        assert_eq!(finder.path().map(|path| path.len() - 1), Some(31));
+        assert_eq!(finder.path().unwrap().first(), Some(&Pos { x: 0, y: 0 }));
+    }
+
     #[test]
     fn test_get_square() {
         let height_map = indoc! {r#"
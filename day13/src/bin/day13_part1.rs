@@ -8,9 +8,11 @@ use nom::IResult;
 use std::cmp::{min, Ordering};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day13/data/input.txt")?);
+    let path = input_path("day13");
+    let r = BufReader::new(File::open(path)?);
     let ret = simulate(r);
     println!("answer: {}", ret);
     Ok(())
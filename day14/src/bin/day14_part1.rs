@@ -8,9 +8,11 @@ use nom::IResult;
 use std::cmp::{max, min};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day14/data/input.txt")?);
+    let path = input_path("day14");
+    let r = BufReader::new(File::open(path)?);
     let ret = count_sand::<600, 200>(r);
     println!("answer: {}", ret);
     Ok(())
@@ -8,12 +8,14 @@ use nom::IResult;
 use std::cmp::{max, min};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use tools::input::input_path;
 
 const WIDTH: usize = 1000;
 const HEIGHT: usize = 200;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day14/data/input.txt")?);
+    let path = input_path("day14");
+    let r = BufReader::new(File::open(path)?);
     let ret = count_sand::<WIDTH, HEIGHT>(r);
     println!("answer: {}", ret);
     Ok(())
@@ -11,9 +11,11 @@ use nom::sequence::{preceded, separated_pair, tuple};
 use nom::IResult;
 
 use day15::CheckResult;
+use tools::input::input_path;
 
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day15/data/input.txt")?);
+    let path = input_path("day15");
+    let r = BufReader::new(File::open(path)?);
 
     let data_list = read_data(r);
 
@@ -25,7 +27,7 @@ fn main() -> anyhow::Result<()> {
             count += 1;
         }
     }
-    println!("count: {}", count);
+    println!("answer: {}", count);
 
     Ok(())
 }
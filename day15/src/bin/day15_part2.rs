@@ -11,35 +11,97 @@ use nom::combinator::{opt, recognize};
 use nom::sequence::{preceded, separated_pair, tuple};
 use nom::IResult;
 
+use budget::{Budget, Checkpoint, Solve, Stats};
+use tools::input::input_path;
+
 fn main() -> anyhow::Result<()> {
-    let r = BufReader::new(File::open("./day15/data/input.txt")?);
+    let path = input_path("day15");
+    let r = BufReader::new(File::open(path)?);
 
     let data_list = read_data(r);
 
-    let mut find_pos = None;
-    for y in 0..=4000000 {
-        println!("y: {}", y);
+    let checkpoint = Checkpoint::from_env("day15_part2", 100000);
+    let budget = Budget::from_env();
+
+    match find_tuning_frequency::<4000000>(&data_list, &budget, checkpoint.as_ref())? {
+        Solve::Done { answer, stats } => {
+            println!("rows: {} elapsed: {:?}", stats.nodes, stats.elapsed());
+            println!("answer: {}", answer);
+        }
+        Solve::TimedOut { stats, .. } => {
+            println!(
+                "timed out: rows: {} elapsed: {:?}",
+                stats.nodes,
+                stats.elapsed()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// 0..=Wの範囲で、ビーコンが存在しうる場所を行ごとに探す。
+/// 予算が切れたら、そこまでの統計を返す。
+/// チェックポイントがあれば、保存されている行から再開する。
+fn find_tuning_frequency<const W: i32>(
+    data_list: &[Data],
+    budget: &Budget,
+    checkpoint: Option<&Checkpoint>,
+) -> anyhow::Result<Solve<u64>> {
+    // 次に調べる行
+    let start = match checkpoint.map(|c| c.load()).transpose()?.flatten() {
+        Some(text) => parse_checkpoint(&text)?,
+        None => 0,
+    };
+    let mut stats = Stats::resume(start as u64);
+
+    for y in start..=W {
+        if let Some(checkpoint) = checkpoint {
+            if checkpoint.is_due(stats.nodes) {
+                checkpoint.save(&format!("row {}\n", y))?;
+            }
+        }
+        if stats.tick(budget) {
+            if let Some(checkpoint) = checkpoint {
+                checkpoint.save(&format!("row {}\n", y))?;
+            }
+            return Ok(Solve::TimedOut { best: None, stats });
+        }
+
         let mut range_list = Vec::new();
-        for data in &data_list {
+        for data in data_list {
             if let Some(r) = data.get_range(y) {
                 range_list.push(r);
             }
         }
 
-        if let Some(k) = find_space::<4000000>(&range_list) {
-            find_pos = Some((k, y));
-            break;
+        if let Some(x) = find_space::<W>(&range_list) {
+            if let Some(checkpoint) = checkpoint {
+                checkpoint.clear()?;
+            }
+
+            let tuning_frequency = x as u64 * 4000000 + y as u64;
+            return Ok(Solve::Done {
+                answer: tuning_frequency,
+                stats,
+            });
         }
     }
 
-    let (x, y) = find_pos.expect("not found");
-    let x = x as u64;
-    let y = y as u64;
-
-    let tuning_frequency = x * 4000000 + y;
-    println!("tuning_frequency: {}", tuning_frequency);
+    // 最後まで調べたので、続きはない
+    if let Some(checkpoint) = checkpoint {
+        checkpoint.clear()?;
+    }
+    anyhow::bail!("no space for the distress beacon in 0..={}", W)
+}
 
-    Ok(())
+/// "row 1234"
+fn parse_checkpoint(text: &str) -> anyhow::Result<i32> {
+    let row = text
+        .trim()
+        .strip_prefix("row ")
+        .ok_or_else(|| anyhow::anyhow!("illegal checkpoint: {:?}", text))?;
+    Ok(row.parse()?)
 }
 
 #[derive(Debug)]
@@ -179,25 +241,57 @@ mod tests {
         let r = include_str!("../../data/sample.txt").as_bytes();
         let data_list = read_data(r);
 
-        let mut find_pos = None;
+        let solve = find_tuning_frequency::<20>(&data_list, &Budget::unlimited(), None).unwrap();
+        assert_eq!(solve.answer(), Some(&56000011));
+        assert_eq!(solve.stats().nodes, 12);
+    }
 
-        for y in 0..=20 {
-            let mut range_list = Vec::new();
-            for data in &data_list {
-                if let Some(r) = data.get_range(y) {
-                    range_list.push(r);
-                }
-            }
+    #[test]
+    fn test_resume() {
+        let r = include_str!("../../data/sample.txt").as_bytes();
+        let data_list = read_data(r);
 
-            if let Some(k) = find_space::<20>(&range_list) {
-                find_pos = Some((k, y));
-                break;
-            }
-        }
+        let path =
+            std::env::temp_dir().join(format!("day15_test_{}.checkpoint", std::process::id()));
+        let checkpoint = Checkpoint::new(&path, 2);
+
+        // 5行で中断
+        let solve =
+            find_tuning_frequency::<20>(&data_list, &Budget::with_max_nodes(5), Some(&checkpoint))
+                .unwrap();
+        assert!(matches!(solve, Solve::TimedOut { best: None, .. }));
+        assert_eq!(checkpoint.load().unwrap(), Some("row 5\n".to_string()));
+
+        // 続きから再開して、同じ答えになること
+        let solve =
+            find_tuning_frequency::<20>(&data_list, &Budget::unlimited(), Some(&checkpoint))
+                .unwrap();
+        assert_eq!(solve.answer(), Some(&56000011));
+        assert_eq!(solve.stats().nodes, 12);
+
+        // 完了したらチェックポイントは消える
+        assert_eq!(checkpoint.load().unwrap(), None);
+    }
+
+    #[test]
+    fn test_not_found() {
+        // 1つのセンサーで全部覆われている
+        let data_list = vec![Data::new((10, 10), (10, 40))];
+
+        let e = find_tuning_frequency::<20>(&data_list, &Budget::unlimited(), None).unwrap_err();
+        assert_eq!(e.to_string(), "no space for the distress beacon in 0..=20");
+    }
+
+    #[test]
+    fn test_timed_out() {
+        let r = include_str!("../../data/sample.txt").as_bytes();
+        let data_list = read_data(r);
+
+        let budget = Budget::unlimited();
+        budget.cancel();
 
-        let (x, y) = find_pos.unwrap();
-        let tuning_frequency = x * 4000000 + y;
-        println!("tuning_frequency: {}", tuning_frequency);
+        let solve = find_tuning_frequency::<20>(&data_list, &budget, None).unwrap();
+        assert!(matches!(solve, Solve::TimedOut { best: None, .. }));
     }
 
     #[test]
@@ -12,9 +12,11 @@ use nom::IResult;
 use petgraph::algo::dijkstra;
 use petgraph::graph::NodeIndex;
 use petgraph::Graph;
+use tools::input::input_path;
 
 fn main() {
-    let r = BufReader::new(File::open("./day16/data/input.txt").unwrap());
+    let path = input_path("day16");
+    let r = BufReader::new(File::open(path).unwrap());
     let searcher = Searcher::from_reader(r);
 
     let mut results: Vec<Route> = Vec::new();
@@ -30,6 +32,7 @@ fn main() {
 
     let best_route = results.iter().max_by_key(|r| r.pressure_released).unwrap();
     println!("result: {:?}", best_route);
+    println!("answer: {}", best_route.pressure_released);
 }
 
 struct Searcher {
@@ -14,17 +14,38 @@ use petgraph::algo::dijkstra;
 use petgraph::graph::NodeIndex;
 use petgraph::Graph;
 
-fn main() {
-    let r = BufReader::new(File::open("./day16/data/input.txt").unwrap());
-
-    let (a, b) = simulate(r);
+use budget::{Budget, Solve, Stats};
+use tools::input::input_path;
 
-    println!("result: {:?}", a);
-    println!("result: {:?}", b);
-    println!("result: {:?}", a.pressure_released + b.pressure_released);
+fn main() {
+    let path = input_path("day16");
+    let r = BufReader::new(File::open(path).unwrap());
+
+    match simulate(r, &Budget::from_env()) {
+        Solve::Done {
+            answer: (a, b),
+            stats,
+        } => {
+            println!("result: {:?}", a);
+            println!("result: {:?}", b);
+            println!("nodes: {} elapsed: {:?}", stats.nodes, stats.elapsed());
+            println!("answer: {}", a.pressure_released + b.pressure_released);
+        }
+        Solve::TimedOut { best, stats } => {
+            if let Some((a, b)) = best {
+                println!("best so far: {}", a.pressure_released + b.pressure_released);
+            }
+            println!(
+                "timed out: nodes: {} elapsed: {:?}",
+                stats.nodes,
+                stats.elapsed()
+            );
+        }
+    }
 }
 
-fn simulate(r: impl BufRead) -> (Route, Route) {
+/// 予算が切れた場合は、それまでのベストを返す。
+fn simulate(r: impl BufRead, budget: &Budget) -> Solve<(Route, Route)> {
     let valves = Valve::from_reader(r);
 
     let initial_route = Route {
@@ -35,6 +56,7 @@ fn simulate(r: impl BufRead) -> (Route, Route) {
     };
 
     let mut best_pattern = None;
+    let mut stats = Stats::new();
 
     // 自分とゾウが担当する組み合わせを洗い出し。
     let patterns = make_patterns(&valves);
@@ -42,17 +64,22 @@ fn simulate(r: impl BufRead) -> (Route, Route) {
     for (i, (target1, target2)) in patterns.into_iter().enumerate() {
         // you
         let mut results1: Vec<Route> = Vec::new();
-        let searcher = Searcher::new(&valves, target1);
-        searcher.search(initial_route.clone(), &mut results1);
+        let searcher = Searcher::new(&valves, target1, budget);
+        searcher.search(initial_route.clone(), &mut results1, &mut stats);
 
         // elephant
         let mut results2: Vec<Route> = Vec::new();
-        let searcher = Searcher::new(&valves, target2);
-        searcher.search(initial_route.clone(), &mut results2);
+        let searcher = Searcher::new(&valves, target2, budget);
+        searcher.search(initial_route.clone(), &mut results2, &mut stats);
 
         // それぞれのベストを合算した結果を使う。
-        let best_route1 = results1.iter().max_by_key(|r| r.pressure_released).unwrap();
-        let best_route2 = results2.iter().max_by_key(|r| r.pressure_released).unwrap();
+        // 途中で打ち切られて結果がない場合は終了。
+        let (Some(best_route1), Some(best_route2)) = (
+            results1.iter().max_by_key(|r| r.pressure_released),
+            results2.iter().max_by_key(|r| r.pressure_released),
+        ) else {
+            break;
+        };
 
         if best_pattern.is_none() {
             best_pattern = Some((best_route1.clone(), best_route2.clone()));
@@ -66,9 +93,13 @@ fn simulate(r: impl BufRead) -> (Route, Route) {
                 best_pattern = Some((best_route1.clone(), best_route2.clone()));
             }
         }
+
+        if stats.is_expired() {
+            break;
+        }
     }
 
-    best_pattern.expect("no pattern")
+    Solve::from_stats(best_pattern, stats)
 }
 
 fn make_patterns(valves: &[Valve]) -> Vec<(HashSet<String>, HashSet<String>)> {
@@ -108,10 +139,16 @@ struct Searcher<'a> {
     distance_map: HashMap<String, HashMap<String, u32>>,
 
     target_valves: HashSet<String>,
+
+    budget: &'a Budget,
 }
 
 impl<'a> Searcher<'a> {
-    fn new(valves: &'a [Valve], target_valves: HashSet<String>) -> Searcher<'a> {
+    fn new(
+        valves: &'a [Valve],
+        target_valves: HashSet<String>,
+        budget: &'a Budget,
+    ) -> Searcher<'a> {
         let valves: HashMap<String, &Valve> = valves.iter().map(|v| (v.name.clone(), v)).collect();
 
         // make graph
@@ -149,6 +186,7 @@ impl<'a> Searcher<'a> {
             valves,
             distance_map,
             target_valves,
+            budget,
         }
     }
 
@@ -192,7 +230,12 @@ impl<'a> Searcher<'a> {
     ///
     /// route: current route
     /// results: result for stock
-    fn search(&self, route: Route, results: &mut Vec<Route>) {
+    /// stats: 予算切れになったら、探索を打ち切る。
+    fn search(&self, route: Route, results: &mut Vec<Route>, stats: &mut Stats) {
+        if stats.tick(self.budget) {
+            return;
+        }
+
         if route.remain_time == 0 {
             // 時間が尽きたら終了
             results.push(route);
@@ -213,7 +256,7 @@ impl<'a> Searcher<'a> {
 
         for route in new_routes {
             // 進めるルートごとに、再帰。
-            self.search(route, results);
+            self.search(route, results, stats);
         }
     }
 }
@@ -287,7 +330,7 @@ mod tests {
     fn test_sample() {
         let r = include_str!("../../data/sample.txt").as_bytes();
 
-        let (a, b) = simulate(r);
+        let (a, b) = simulate(r, &Budget::unlimited()).answer().cloned().unwrap();
 
         println!("{:?}", a);
         println!("{:?}", b);
@@ -296,6 +339,18 @@ mod tests {
         assert_eq!(b.path, vec!["AA", "DD", "HH", "EE"]);
     }
 
+    #[test]
+    fn test_timed_out() {
+        let r = include_str!("../../data/sample.txt").as_bytes();
+
+        let budget = Budget::unlimited();
+        budget.cancel();
+
+        let solve = simulate(r, &budget);
+        assert!(matches!(solve, Solve::TimedOut { best: None, .. }));
+        assert_eq!(solve.stats().nodes, 0);
+    }
+
     #[test]
     fn test_read_valves() {
         let valves = Valve::from_reader(include_str!("../../data/sample.txt").as_bytes());
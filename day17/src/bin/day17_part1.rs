@@ -1,15 +1,17 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use tools::input::input_path;
 
 fn main() {
-    let r = BufReader::new(File::open("./day17/data/input.txt").unwrap());
+    let path = input_path("day17");
+    let r = BufReader::new(File::open(path).unwrap());
 
     let directions = GasDirection::from_reader(r);
     let mut stage: Stage<7> = Stage::new(directions);
     for _ in 0..2022 {
         stage.round();
     }
-    println!("tall:{}", stage.highest_point + 1)
+    println!("answer: {}", stage.highest_point + 1)
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -3,9 +3,11 @@ extern crate core;
 use itertools::Itertools;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use tools::input::input_path;
 
 fn main() {
-    let r = BufReader::new(File::open("./day17/data/input.txt").unwrap());
+    let path = input_path("day17");
+    let r = BufReader::new(File::open(path).unwrap());
 
     let directions = GasDirection::from_reader(r);
 
@@ -19,7 +21,7 @@ fn main() {
 
     // 繰り返しパターンになっているので、繰り返し位置から答えを計算する。
     let tall = analyzer.simulate::<2000, 10, 5000>(1000000000000);
-    println!("tall:{}", tall);
+    println!("answer: {}", tall);
 }
 
 #[derive(Debug, Copy, Clone)]
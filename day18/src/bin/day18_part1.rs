@@ -2,11 +2,13 @@ use day18::simulate::Simulator;
 use day18::Vertex3;
 use std::fs::File;
 use std::io::BufReader;
+use tools::input::input_path;
 
 fn main() {
-    let r = BufReader::new(File::open("./day18/data/input.txt").unwrap());
+    let path = input_path("day18");
+    let r = BufReader::new(File::open(path).unwrap());
     let cubes = Vertex3::from_reader(r).unwrap();
     let simulator = Simulator::new(&cubes, false);
     let total = simulator.simulate();
-    println!("total: {}", total);
+    println!("answer: {}", total);
 }
@@ -1,11 +1,15 @@
+use std::fs;
+
 use rayon::prelude::*;
 
 use day19::Stone::Geode;
 use day19::{Blueprint, Processor, State};
+use tools::input::input_path;
 
 fn main() {
-    let input = include_str!("../../data/input.txt");
-    let blueprints = Blueprint::parse_input(input);
+    let path = input_path("day19");
+    let input = fs::read_to_string(path).unwrap();
+    let blueprints = Blueprint::parse_input(&input);
 
     let score: u32 = blueprints
         .par_iter()
@@ -20,5 +24,5 @@ fn main() {
         })
         .sum();
 
-    println!("Score: {}", score);
+    println!("answer: {}", score);
 }
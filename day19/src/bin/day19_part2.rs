@@ -1,26 +1,43 @@
+use std::fs;
+
 use rayon::prelude::*;
 
+use budget::{Budget, Checkpoint, Solve};
 use day19::{Blueprint, Processor, State, Stone};
+use tools::input::input_path;
 use Stone::Geode;
 
 fn main() {
-    let input = include_str!("../../data/input.txt");
-    let blueprints = Blueprint::parse_input(input);
+    let path = input_path("day19");
+    let input = fs::read_to_string(path).unwrap();
+    let blueprints = Blueprint::parse_input(&input);
+
+    let budget = Budget::from_env();
 
-    let score: i32 = blueprints
+    let solves: Vec<Solve<i32>> = blueprints
         .par_iter()
         .take(3)
         .map(|blueprint| {
-            let mut max = State::new(0);
-            let mut process = Processor::new(blueprint);
-            process.dfs(State::new(32), &mut max);
-
-            let best = max.get_resource(&Geode);
-            println!("id:{} {:?}", blueprint.id, max);
+            let mut process = Processor::with_budget(blueprint, budget.clone());
+            let checkpoint =
+                Checkpoint::from_env(&format!("day19_part2_{}", blueprint.id), 10_000_000);
+            let solve = process
+                .solve_with_checkpoint(State::new(32), checkpoint.as_ref())
+                .unwrap()
+                .map(|max| max.get_resource(&Geode));
+            println!("id:{} {:?}", blueprint.id, solve);
 
-            best
+            solve
         })
-        .product();
+        .collect();
 
-    println!("Score: {}", score);
+    let nodes: u64 = solves.iter().map(|s| s.stats().nodes).sum();
+    if solves.iter().all(|s| s.answer().is_some()) {
+        let score: i32 = solves.iter().filter_map(|s| s.answer()).product();
+        println!("nodes: {}", nodes);
+        println!("answer: {}", score);
+    } else {
+        let best: i32 = solves.iter().map(|s| *s.best().unwrap_or(&0)).product();
+        println!("timed out: best so far: {} nodes: {}", best, nodes);
+    }
 }
@@ -9,10 +9,13 @@ use nom::multi::separated_list1;
 use nom::sequence::{delimited, separated_pair};
 use nom::IResult;
 
+use budget::{Budget, Checkpoint, Solve, Stats};
 use Stone::{Clay, Geode, Obsidian, Ore};
 
 type Key = (i32, i32, i32, i32, i32, i32, i32, i32, i32);
 
+const STONES: [Stone; 4] = [Ore, Clay, Obsidian, Geode];
+
 pub struct Processor<'a> {
     blueprint: &'a Blueprint,
     // この時間で保持している最大値
@@ -23,10 +26,20 @@ pub struct Processor<'a> {
     need_obsidian: i32,
 
     cache: HashSet<Key>,
+    // 探索待ちのステート
+    stack: Vec<State>,
+
+    budget: Budget,
+    stats: Stats,
 }
 
 impl<'a> Processor<'a> {
     pub fn new(blueprint: &'a Blueprint) -> Processor<'a> {
+        Self::with_budget(blueprint, Budget::unlimited())
+    }
+
+    /// 予算が切れたら、探索を打ち切る。
+    pub fn with_budget(blueprint: &'a Blueprint, budget: Budget) -> Processor<'a> {
         let need_ore = *blueprint
             .costs
             .values()
@@ -85,7 +98,47 @@ impl<'a> Processor<'a> {
             need_clay,
             need_obsidian,
             cache: HashSet::new(),
+            stack: Vec::new(),
+            budget,
+            stats: Stats::new(),
+        }
+    }
+
+    /// 探索して、Geodeが最大になった状態を返す。
+    /// 予算切れの場合は、それまでの最大を返す。
+    pub fn solve(&mut self, initial: State) -> Solve<State> {
+        self.solve_with_checkpoint(initial, None)
+            .expect("no io without checkpoint")
+    }
+
+    /// チェックポイントがあれば、保存されている探索待ちのステートから再開する。
+    /// キャッシュは保存しないので、再開後に探索しながら作り直す。
+    /// 予算切れの場合は、その時点の状態を保存する。
+    pub fn solve_with_checkpoint(
+        &mut self,
+        initial: State,
+        checkpoint: Option<&Checkpoint>,
+    ) -> anyhow::Result<Solve<State>> {
+        let mut max = State::new(0);
+        match checkpoint.map(|c| c.load()).transpose()?.flatten() {
+            Some(text) => self.restore(&text, &mut max)?,
+            None => self.stack.push(initial),
+        }
+
+        self.run(&mut max, checkpoint)?;
+
+        if let Some(checkpoint) = checkpoint {
+            if self.stats.is_expired() {
+                checkpoint.save(&self.to_checkpoint(&max))?;
+            } else {
+                checkpoint.clear()?;
+            }
         }
+
+        Ok(Solve::from_stats(
+            Some(max),
+            std::mem::take(&mut self.stats),
+        ))
     }
 
     fn collect(&self, state: &mut State) {
@@ -128,7 +181,45 @@ impl<'a> Processor<'a> {
         Some(new_state)
     }
 
-    pub fn dfs(&mut self, mut state: State, max: &mut State) {
+    pub fn dfs(&mut self, state: State, max: &mut State) {
+        self.stack.push(state);
+        self.run(max, None).expect("no io without checkpoint");
+    }
+
+    /// 探索待ちがなくなるか、予算が切れるまで探索する。
+    /// 再帰と同じ順番になるよう、子は逆順に積み、キャッシュは取り出すときに確認する。
+    fn run(&mut self, max: &mut State, checkpoint: Option<&Checkpoint>) -> anyhow::Result<()> {
+        let mut is_root = true;
+        while let Some(state) = self.stack.pop() {
+            if !is_root {
+                let key = state.key();
+                if self.cache.contains(&key) {
+                    continue;
+                }
+                self.cache.insert(key);
+            }
+            is_root = false;
+
+            if let Some(checkpoint) = checkpoint {
+                if checkpoint.is_due(self.stats.nodes) {
+                    self.stack.push(state.clone());
+                    checkpoint.save(&self.to_checkpoint(max))?;
+                    self.stack.pop();
+                }
+            }
+
+            if self.stats.tick(&self.budget) {
+                self.stack.push(state);
+                break;
+            }
+
+            self.visit(state, max);
+        }
+
+        Ok(())
+    }
+
+    fn visit(&mut self, mut state: State, max: &mut State) {
         // 残り時間にたいして、既にGeodeロボットの所持数がすくない場合、処理しない。
         let geode_possible =
             (state.get_robot_count(&Geode) * state.left_time) + state.get_resource(&Geode);
@@ -176,27 +267,54 @@ impl<'a> Processor<'a> {
             }
         }
 
-        for state in new_states {
-            let key = (
-                state.left_time,
-                state.get_resource(&Geode),
-                state.get_resource(&Ore),
-                state.get_resource(&Clay),
-                state.get_resource(&Obsidian),
-                state.get_robot_count(&Geode),
-                state.get_robot_count(&Ore),
-                state.get_robot_count(&Clay),
-                state.get_robot_count(&Obsidian),
-            );
-
-            if self.cache.contains(&key) {
-                continue;
-            }
+        // 先頭から探索するので、逆順に積む。
+        self.stack.extend(new_states.into_iter().rev());
+    }
 
-            self.cache.insert(key);
+    /// nodes 12345
+    /// max <state>
+    /// stack <state>
+    ///
+    /// キャッシュは枝刈りにしか使わないので保存しない。
+    /// 大きさは探索待ちのステートの数(深さ×分岐数)に比例する。
+    fn to_checkpoint(&self, max: &State) -> String {
+        let mut lines = vec![
+            format!("nodes {}", self.stats.nodes),
+            format!("max {}", max.to_checkpoint()),
+        ];
+        for state in &self.stack {
+            lines.push(format!("stack {}", state.to_checkpoint()));
+        }
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    fn restore(&mut self, text: &str, max: &mut State) -> anyhow::Result<()> {
+        let illegal = || anyhow::anyhow!("illegal checkpoint line");
+
+        self.stack.clear();
+        self.cache.clear();
+
+        for line in text.lines() {
+            let (tag, values) = line.split_once(' ').ok_or_else(illegal)?;
+            let values: Vec<i32> = values
+                .split_whitespace()
+                .map(|v| v.parse())
+                .collect::<Result<_, _>>()?;
 
-            self.dfs(state, max);
+            match tag {
+                "nodes" => {
+                    let nodes = *values.first().ok_or_else(illegal)?;
+                    self.stats = Stats::resume(nodes as u64);
+                }
+                "max" => *max = State::from_checkpoint(&values).ok_or_else(illegal)?,
+                "stack" => self
+                    .stack
+                    .push(State::from_checkpoint(&values).ok_or_else(illegal)?),
+                _ => return Err(illegal()),
+            }
         }
+
+        Ok(())
     }
 }
 
@@ -227,6 +345,57 @@ impl State {
     fn get_robot_count(&self, robot: &Stone) -> i32 {
         *self.robots.get(robot).unwrap_or(&0)
     }
+
+    fn key(&self) -> Key {
+        (
+            self.left_time,
+            self.get_resource(&Geode),
+            self.get_resource(&Ore),
+            self.get_resource(&Clay),
+            self.get_resource(&Obsidian),
+            self.get_robot_count(&Geode),
+            self.get_robot_count(&Ore),
+            self.get_robot_count(&Clay),
+            self.get_robot_count(&Obsidian),
+        )
+    }
+
+    /// 残り時間、資源(ore, clay, obsidian, geode)、ロボット(同順)の9つの数値
+    fn to_checkpoint(&self) -> String {
+        let mut values = vec![self.left_time];
+        values.extend(STONES.iter().map(|s| self.get_resource(s)));
+        values.extend(STONES.iter().map(|s| self.get_robot_count(s)));
+        values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn from_checkpoint(values: &[i32]) -> Option<Self> {
+        let [left_time, ref rest @ ..] = values[..] else {
+            return None;
+        };
+        if rest.len() != STONES.len() * 2 {
+            return None;
+        }
+
+        let (resources, robots) = rest.split_at(STONES.len());
+        let to_map = |counts: &[i32]| {
+            STONES
+                .iter()
+                .zip(counts)
+                .filter(|(_, n)| **n != 0)
+                .map(|(s, n)| (*s, *n))
+                .collect()
+        };
+
+        Some(Self {
+            robots: to_map(robots),
+            resources: to_map(resources),
+            left_time,
+        })
+    }
 }
 
 impl State {}
@@ -352,6 +521,91 @@ mod tests {
         assert_eq!(processor.create_robot(&state, Geode), None);
     }
 
+    #[test]
+    fn test_solve() {
+        let blueprints = Blueprint::parse_input(include_str!("../data/sample.txt"));
+
+        let mut processor = Processor::new(&blueprints[0]);
+        let solve = processor.solve(State::new(19));
+        assert_eq!(solve.answer().map(|s| s.get_resource(&Geode)), Some(1));
+
+        // 中断した場合は、打ち切られた結果になること
+        let budget = Budget::unlimited();
+        budget.cancel();
+        let mut processor = Processor::with_budget(&blueprints[0], budget);
+        let solve = processor.solve(State::new(24));
+        assert!(matches!(solve, Solve::TimedOut { best: Some(_), .. }));
+        assert_eq!(solve.stats().nodes, 0);
+    }
+
+    #[test]
+    fn test_resume() {
+        let blueprints = Blueprint::parse_input(include_str!("../data/sample.txt"));
+
+        let path =
+            std::env::temp_dir().join(format!("day19_test_{}.checkpoint", std::process::id()));
+        let checkpoint = Checkpoint::new(&path, 10000);
+
+        // 途中で中断
+        let mut processor = Processor::with_budget(&blueprints[0], Budget::with_max_nodes(25000));
+        let solve = processor
+            .solve_with_checkpoint(State::new(19), Some(&checkpoint))
+            .unwrap();
+        assert!(matches!(solve, Solve::TimedOut { .. }));
+        assert!(checkpoint.load().unwrap().is_some());
+
+        // キャッシュは保存せず、探索待ちのステートだけ
+        let text = checkpoint.load().unwrap().unwrap();
+        assert!(text.lines().all(|line| !line.starts_with("cache")));
+        let saved = text
+            .lines()
+            .filter(|line| line.starts_with("stack"))
+            .count();
+        assert!(saved > 0 && saved < 100, "{} states", saved);
+
+        // 別のプロセッサで再開し、途中でまた中断して保存する
+        let mut processor = Processor::with_budget(&blueprints[0], Budget::with_max_nodes(50000));
+        let solve = processor
+            .solve_with_checkpoint(State::new(19), Some(&checkpoint))
+            .unwrap();
+        assert!(matches!(solve, Solve::TimedOut { .. }));
+        assert_ne!(checkpoint.load().unwrap(), Some(text));
+
+        // その保存から再開して、最後まで探索すると同じ答えになること
+        let mut processor = Processor::new(&blueprints[0]);
+        let solve = processor
+            .solve_with_checkpoint(State::new(19), Some(&checkpoint))
+            .unwrap();
+
+        let mut expected = Processor::new(&blueprints[0]);
+        let expected = expected.solve(State::new(19));
+
+        assert!(solve.answer().is_some());
+        assert_eq!(
+            solve.answer().map(|s| s.get_resource(&Geode)),
+            expected.answer().map(|s| s.get_resource(&Geode))
+        );
+        // キャッシュを作り直す分だけ、中断しないときより多く探索する
+        assert!(solve.stats().nodes >= expected.stats().nodes);
+        assert_eq!(checkpoint.load().unwrap(), None);
+    }
+
+    #[test]
+    fn test_state_checkpoint() {
+        let state = State {
+            robots: vec![(Ore, 1), (Geode, 2)].into_iter().collect(),
+            resources: vec![(Clay, 14)].into_iter().collect(),
+            left_time: 10,
+        };
+
+        let text = state.to_checkpoint();
+        assert_eq!(text, "10 0 14 0 0 1 0 0 2");
+
+        let values: Vec<i32> = text.split(' ').map(|v| v.parse().unwrap()).collect();
+        assert_eq!(State::from_checkpoint(&values), Some(state));
+        assert_eq!(State::from_checkpoint(&values[1..]), None);
+    }
+
     #[test]
     fn test_parse_blueprints() {
         let input = include_str!("../data/sample.txt");
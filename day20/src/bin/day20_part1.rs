@@ -1,9 +1,13 @@
 use std::fmt::Debug;
+use std::fs;
 use std::io::BufRead;
 use std::str::FromStr;
+use tools::input::input_path;
 
 fn main() {
-    let r = include_str!("../../data/input.txt").as_bytes();
+    let path = input_path("day20");
+    let input = fs::read_to_string(path).unwrap();
+    let r = input.as_bytes();
 
     let list = read_input(r);
     let mut mixer = Mixer::new(list);
@@ -15,6 +19,7 @@ fn main() {
     let sum: i64 = numbers.iter().sum();
 
     println!("result: {:?} => {}", numbers, sum);
+    println!("answer: {}", sum);
 }
 
 fn read_input<T>(r: impl BufRead) -> Vec<T>
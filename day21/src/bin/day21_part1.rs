@@ -1,12 +1,17 @@
+use std::fs;
+
 use day21::{read_expr, Calculator};
+use tools::input::input_path;
 
 fn main() {
-    let r = include_str!("../../data/input.txt").as_bytes();
+    let path = input_path("day21");
+    let input = fs::read_to_string(path).unwrap();
+    let r = input.as_bytes();
 
     let expr_list = read_expr(r);
     let calculator = Calculator::new(expr_list);
 
-    println!("result: {:?}", calculator.calc("root"));
+    println!("answer: {}", calculator.calc("root").expect("failed to calc"));
 }
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::fs;
 use std::io::BufRead;
 use std::ops::Range;
 
@@ -8,12 +9,15 @@ use nom::character::complete;
 use nom::combinator::{map, value};
 use nom::multi::many1;
 use nom::IResult;
+use tools::input::input_path;
 
 fn main() {
-    let r = include_str!("../../data/input.txt").as_bytes();
+    let path = input_path("day22");
+    let input = fs::read_to_string(path).unwrap();
+    let r = input.as_bytes();
 
     let password = simulate(r);
-    println!("password:{:?}", password);
+    println!("answer: {}", password);
 }
 
 fn simulate(r: impl BufRead) -> i32 {
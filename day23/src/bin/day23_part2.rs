@@ -1,10 +1,15 @@
+use std::fs;
+
 use day23::State;
+use tools::input::input_path;
 
 fn main() {
-    let r = include_str!("../../data/input.txt").as_bytes();
+    let path = input_path("day23");
+    let input = fs::read_to_string(path).unwrap();
+    let r = input.as_bytes();
 
     let mut state = State::read_from(r);
     while state.do_round() {}
 
-    println!("answer:{:?}", state.get_round());
+    println!("answer: {}", state.get_round());
 }
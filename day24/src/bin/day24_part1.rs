@@ -1,12 +1,16 @@
 use num::integer::Roots;
 use std::collections::{HashSet, VecDeque};
+use std::fs;
 use std::io::BufRead;
+use tools::input::input_path;
 
 fn main() {
-    let r = include_str!("../../data/input.txt").as_bytes();
+    let path = input_path("day24");
+    let input = fs::read_to_string(path).unwrap();
+    let r = input.as_bytes();
     let stage = Stage::read(r);
     if let Some(state) = bfs(&stage) {
-        println!("minutes:{}", state.minutes + 1);
+        println!("answer: {}", state.minutes + 1);
     }
 }
 
@@ -1,9 +1,13 @@
 use num::integer::Roots;
 use std::collections::{HashSet, VecDeque};
+use std::fs;
 use std::io::BufRead;
+use tools::input::input_path;
 
 fn main() {
-    let r = include_str!("../../data/input.txt").as_bytes();
+    let path = input_path("day24");
+    let input = fs::read_to_string(path).unwrap();
+    let r = input.as_bytes();
     let stage = Stage::read(r);
 
     let start = Point { x: 0, y: -1 };
@@ -33,6 +37,7 @@ fn main() {
     s.target_pos = goal;
     let state = s.bfs().expect("no solution found");
     println!("step3 {:?}", state);
+    println!("answer: {}", state.minutes);
 }
 
 struct Searcher<'a> {
@@ -1,14 +1,18 @@
 use itertools::Itertools;
+use std::fs;
 use std::io::BufRead;
+use tools::input::input_path;
 
 fn main() {
-    let r = include_str!("../../data/input.txt").as_bytes();
+    let path = input_path("day25");
+    let input = fs::read_to_string(path).unwrap();
+    let r = input.as_bytes();
 
     let sum: i64 = r.lines().flatten().map(|l| convert_to_normal(&l)).sum();
     println!("sum: {}", sum);
 
     let sum_snafu = convert_to_snafu(sum);
-    println!("sum_snafu: {}", sum_snafu);
+    println!("answer: {}", sum_snafu);
 }
 
 fn convert_to_normal(n_snafu: &str) -> i64 {
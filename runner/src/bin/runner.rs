@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use anyhow::{anyhow, bail};
+
+use runner::{
+    answers_path, find_inputs, input_name, parse_answer, parse_report, plan_jobs, read_answers,
+    render_json, render_matrix, Cell, Job, Outcome,
+};
+
+const USAGE: &str = "usage: runner <inputs directory> [--day N] [--jobs N] [--json]";
+
+fn main() -> anyhow::Result<()> {
+    let mut dir = None;
+    let mut only_day: Option<u32> = None;
+    let mut workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut json = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" => only_day = Some(args.next().ok_or_else(|| anyhow!(USAGE))?.parse()?),
+            "--jobs" => workers = args.next().ok_or_else(|| anyhow!(USAGE))?.parse()?,
+            "--json" => json = true,
+            _ => dir = Some(PathBuf::from(arg)),
+        }
+    }
+    let dir = dir.ok_or_else(|| anyhow!(USAGE))?;
+
+    // 各日のバイナリは、runnerと同じディレクトリにビルドされている前提。
+    let bin_dir = env::current_exe()?
+        .parent()
+        .ok_or_else(|| anyhow!("failed to get bin directory"))?
+        .to_path_buf();
+
+    let inputs = find_inputs(&dir)?;
+    let jobs =
+        plan_jobs(&inputs, only_day, |job| bin_path(&bin_dir, job).exists()).map_err(|e| {
+            anyhow!(
+                "{} (inputs: {}, binaries: {})",
+                e,
+                dir.display(),
+                bin_dir.display()
+            )
+        })?;
+
+    let outcomes = run_all(&jobs, &bin_dir, workers.max(1), json);
+
+    let mut mismatches = 0;
+    let mut days = Vec::new();
+    for (day, files) in &inputs {
+        if only_day.is_some_and(|d| d != *day) {
+            continue;
+        }
+
+        let mut rows = Vec::new();
+        for input in files {
+            let expected = fs::read_to_string(answers_path(input))
+                .map(|text| read_answers(&text))
+                .unwrap_or_default();
+
+            let cells: BTreeMap<u32, Cell> = jobs
+                .iter()
+                .zip(&outcomes)
+                .filter(|(job, _)| job.day == *day && &job.input == input)
+                .map(|(job, (outcome, report))| {
+                    let cell = Cell {
+                        outcome: outcome.clone(),
+                        expected: expected.get(&job.part).cloned(),
+                        report: report.clone(),
+                    };
+                    (job.part, cell)
+                })
+                .collect();
+
+            mismatches += cells.values().filter(|c| c.is_mismatch()).count();
+
+            rows.push((input_name(input), cells));
+        }
+
+        days.push((*day, rows));
+    }
+
+    if json {
+        // エラーの内容もJSONに入っている
+        println!("{}", render_json(&days));
+    } else {
+        for (day, rows) in &days {
+            println!("{}", render_matrix(*day, rows));
+        }
+
+        for (job, outcome) in jobs.iter().zip(&outcomes) {
+            if let (Outcome::Failed(message), _) = outcome {
+                println!("{} {}: {}", job.bin_name(), job.input.display(), message);
+            }
+        }
+    }
+
+    if mismatches > 0 {
+        bail!("{} answer(s) did not match", mismatches);
+    }
+
+    Ok(())
+}
+
+fn bin_path(bin_dir: &Path, job: &Job) -> PathBuf {
+    bin_dir.join(format!("{}{}", job.bin_name(), env::consts::EXE_SUFFIX))
+}
+
+/// ジョブを並列に実行する。
+/// json のときは、各バイナリが出す集計のJSONも集める。
+fn run_all(
+    jobs: &[Job],
+    bin_dir: &Path,
+    workers: usize,
+    json: bool,
+) -> Vec<(Outcome, Option<String>)> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; jobs.len()]);
+
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let Some(job) = jobs.get(i) else {
+                    break;
+                };
+
+                let outcome = run(job, bin_dir, json);
+                results.lock().unwrap()[i] = Some(outcome);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|o| o.expect("job was not run"))
+        .collect()
+}
+
+fn run(job: &Job, bin_dir: &Path, json: bool) -> (Outcome, Option<String>) {
+    let mut command = Command::new(bin_path(bin_dir, job));
+    command.arg(&job.input);
+    if json {
+        command.arg("--json");
+    }
+
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) => return (Outcome::Failed(e.to_string()), None),
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().last().unwrap_or("failed").to_string();
+        return (Outcome::Failed(message), None);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let outcome = match parse_answer(&stdout) {
+        Some(answer) => Outcome::Answer(answer),
+        None => Outcome::NoAnswer,
+    };
+    (outcome, parse_report(&stdout))
+}
@@ -0,0 +1,440 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::bail;
+
+pub use tools::answers::read_answers;
+
+/// 実行単位 (日, パート, 入力ファイル)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Job {
+    pub day: u32,
+    pub part: u32,
+    pub input: PathBuf,
+}
+
+impl Job {
+    /// 実行するバイナリ名 "day01_part1"
+    pub fn bin_name(&self) -> String {
+        format!("day{:02}_part{}", self.day, self.part)
+    }
+}
+
+/// 実行結果
+#[derive(Debug, PartialEq, Clone)]
+pub enum Outcome {
+    Answer(String),
+    NoAnswer,
+    Failed(String),
+}
+
+/// 入力ごとの結果と、期待値
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cell {
+    pub outcome: Outcome,
+    pub expected: Option<String>,
+    /// バイナリが出力した `report:` のJSON
+    pub report: Option<String>,
+}
+
+impl Cell {
+    pub fn is_mismatch(&self) -> bool {
+        match (&self.outcome, &self.expected) {
+            (_, None) => false,
+            (Outcome::Answer(answer), Some(expected)) => answer != expected,
+            (_, Some(_)) => true,
+        }
+    }
+
+    /// JSONのオブジェクトの中身(括弧なし)
+    fn json_fields(&self) -> String {
+        let (status, answer, error) = match &self.outcome {
+            Outcome::Answer(answer) => ("answer", Some(answer), None),
+            Outcome::NoAnswer => ("no_answer", None, None),
+            Outcome::Failed(message) => ("failed", None, Some(message)),
+        };
+        format!(
+            r#""status":"{}","answer":{},"error":{},"expected":{},"mismatch":{},"report":{}"#,
+            status,
+            json_option(answer),
+            json_option(error),
+            json_option(self.expected.as_ref()),
+            self.is_mismatch(),
+            self.report.as_deref().unwrap_or("null"),
+        )
+    }
+
+    fn render(&self) -> String {
+        let text = match &self.outcome {
+            Outcome::Answer(answer) => answer.clone(),
+            Outcome::NoAnswer => "?".to_string(),
+            Outcome::Failed(_) => "error".to_string(),
+        };
+
+        match &self.expected {
+            Some(expected) if self.is_mismatch() => format!("{} (!= {})", text, expected),
+            _ => text,
+        }
+    }
+}
+
+/// 表の1行。入力ファイル名と、パートごとの結果。
+pub type Row = (String, BTreeMap<u32, Cell>);
+
+/// 入力ディレクトリから日ごとの入力ファイルを探す。
+/// `<dir>/day01/*.txt` の構成を想定。
+pub fn find_inputs(dir: &Path) -> anyhow::Result<BTreeMap<u32, Vec<PathBuf>>> {
+    let mut inputs = BTreeMap::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(day) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("day"))
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        let mut files: Vec<PathBuf> = fs::read_dir(&path)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|ext| ext == "txt"))
+            .collect();
+        files.sort();
+
+        if !files.is_empty() {
+            inputs.insert(day, files);
+        }
+    }
+
+    Ok(inputs)
+}
+
+/// 入力ごと、パートごとのジョブを作る。
+/// has_bin でバイナリがあるものだけに絞り、1つもなければエラーにする。
+pub fn plan_jobs(
+    inputs: &BTreeMap<u32, Vec<PathBuf>>,
+    only_day: Option<u32>,
+    has_bin: impl Fn(&Job) -> bool,
+) -> anyhow::Result<Vec<Job>> {
+    let days: Vec<(&u32, &Vec<PathBuf>)> = inputs
+        .iter()
+        .filter(|(day, _)| only_day.is_none_or(|d| d == **day))
+        .collect();
+    if days.is_empty() {
+        bail!("no input files found");
+    }
+
+    let jobs: Vec<Job> = days
+        .into_iter()
+        .flat_map(|(day, files)| {
+            (1..=2).flat_map(move |part| {
+                files.iter().map(move |input| Job {
+                    day: *day,
+                    part,
+                    input: input.clone(),
+                })
+            })
+        })
+        .filter(|job| has_bin(job))
+        .collect();
+    if jobs.is_empty() {
+        bail!("no day binaries found; build them with `cargo build --release --workspace`");
+    }
+
+    Ok(jobs)
+}
+
+/// 入力ファイル名から拡張子を除いたもの
+pub fn input_name(input: &Path) -> String {
+    input
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// 入力ファイルに対応する回答ファイルのパス
+/// alice.txt => alice.answers
+pub fn answers_path(input: &Path) -> PathBuf {
+    input.with_extension("answers")
+}
+
+/// 標準出力から回答を取り出す。
+/// 最後の "answer: xxx" の行を回答とみなす。
+pub fn parse_answer(stdout: &str) -> Option<String> {
+    stdout
+        .lines()
+        .rev()
+        .find_map(|line| line.strip_prefix("answer:"))
+        .map(|s| s.trim().to_string())
+}
+
+/// 標準出力から、`--json` のときにバイナリが出す集計のJSONを取り出す。
+/// 最後の "report: {...}" の行を、そのままJSONとして扱う。
+pub fn parse_report(stdout: &str) -> Option<String> {
+    stdout
+        .lines()
+        .rev()
+        .find_map(|line| line.strip_prefix("report:"))
+        .map(|s| s.trim().to_string())
+}
+
+/// 日ごとの結果を表にする。
+/// 行が入力ファイル、列がパート。どの行にもあるパートを列にし、ない欄は "-"。
+pub fn render_matrix(day: u32, rows: &[Row]) -> String {
+    let parts: BTreeSet<u32> = rows
+        .iter()
+        .flat_map(|(_, cells)| cells.keys().copied())
+        .collect();
+
+    let mut table = vec![{
+        let mut header = vec![format!("day{:02}", day)];
+        header.extend(parts.iter().map(|p| format!("part{}", p)));
+        header
+    }];
+    for (name, cells) in rows {
+        let mut row = vec![name.clone()];
+        row.extend(
+            parts
+                .iter()
+                .map(|p| cells.get(p).map_or("-".to_string(), |c| c.render())),
+        );
+        table.push(row);
+    }
+
+    // 列ごとの幅をそろえる
+    let widths: Vec<usize> = (0..=parts.len())
+        .map(|i| {
+            table
+                .iter()
+                .filter_map(|row| row.get(i))
+                .map(|s| s.len())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut text = String::new();
+    for row in table {
+        let line: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, s)| format!("{:width$}", s, width = widths[i]))
+            .collect();
+        text.push_str(line.join("  ").trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// 全部の結果をJSONにする。1要素が1つの (日, 入力, パート)。
+pub fn render_json(days: &[(u32, Vec<Row>)]) -> String {
+    let results: Vec<String> = days
+        .iter()
+        .flat_map(|(day, rows)| {
+            rows.iter().flat_map(move |(name, cells)| {
+                cells.iter().map(move |(part, cell)| {
+                    format!(
+                        r#"{{"day":{},"input":{},"part":{},{}}}"#,
+                        day,
+                        json_string(name),
+                        part,
+                        cell.json_fields(),
+                    )
+                })
+            })
+        })
+        .collect();
+    format!("[{}]", results.join(","))
+}
+
+fn json_option(value: Option<&String>) -> String {
+    value.map_or("null".to_string(), |s| json_string(s))
+}
+
+fn json_string(s: &str) -> String {
+    let mut text = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => text.push_str("\\\""),
+            '\\' => text.push_str("\\\\"),
+            '\n' => text.push_str("\\n"),
+            c if c.is_control() => text.push_str(&format!("\\u{:04x}", c as u32)),
+            c => text.push(c),
+        }
+    }
+    text.push('"');
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_answer() {
+        let stdout = indoc! {"
+            y: 1
+            answer: 10
+            result: Route
+            answer: 56000011
+        "};
+        assert_eq!(parse_answer(stdout), Some("56000011".to_string()));
+        assert_eq!(parse_answer("tall:3068\n"), None);
+
+        let stdout = indoc! {r#"
+            report: {"elves":[],"top3":[],"mean":null,"median":null}
+            answer: 0
+        "#};
+        assert_eq!(
+            parse_report(stdout).as_deref(),
+            Some(r#"{"elves":[],"top3":[],"mean":null,"median":null}"#)
+        );
+        assert_eq!(parse_report("answer: 0\n"), None);
+    }
+
+    #[test]
+    fn test_render_matrix() {
+        let answer = |a: &str, e: Option<&str>| Cell {
+            outcome: Outcome::Answer(a.to_string()),
+            expected: e.map(|s| s.to_string()),
+            report: None,
+        };
+
+        let rows = vec![
+            (
+                "alice".to_string(),
+                BTreeMap::from([(1, answer("6032", Some("6032"))), (2, answer("5031", None))]),
+            ),
+            (
+                "bob".to_string(),
+                BTreeMap::from([
+                    (1, answer("1", Some("2"))),
+                    (
+                        2,
+                        Cell {
+                            outcome: Outcome::Failed("panicked".to_string()),
+                            expected: None,
+                            report: None,
+                        },
+                    ),
+                ]),
+            ),
+        ];
+
+        assert!(!rows[0].1[&1].is_mismatch());
+        assert!(rows[1].1[&1].is_mismatch());
+
+        assert_eq!(
+            render_matrix(22, &rows),
+            indoc! {"
+                day22  part1     part2
+                alice  6032      5031
+                bob    1 (!= 2)  error
+            "}
+        );
+
+        // part1 がなくても part2 は part2 の列に出る
+        let rows = vec![
+            (
+                "alice".to_string(),
+                BTreeMap::from([(2, answer("5031", None))]),
+            ),
+            ("bob".to_string(), BTreeMap::new()),
+        ];
+        assert_eq!(
+            render_matrix(22, &rows),
+            indoc! {"
+                day22  part2
+                alice  5031
+                bob    -
+            "}
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let days = vec![(
+            7,
+            vec![(
+                "alice".to_string(),
+                BTreeMap::from([
+                    (
+                        1,
+                        Cell {
+                            outcome: Outcome::Answer("95437".to_string()),
+                            expected: Some("95437".to_string()),
+                            report: Some(r#"{"total":48381165}"#.to_string()),
+                        },
+                    ),
+                    (
+                        2,
+                        Cell {
+                            outcome: Outcome::Failed("no \"/\" dir".to_string()),
+                            expected: Some("24933642".to_string()),
+                            report: None,
+                        },
+                    ),
+                ]),
+            )],
+        )];
+
+        assert_eq!(
+            render_json(&days),
+            concat!(
+                r#"[{"day":7,"input":"alice","part":1,"status":"answer","answer":"95437","error":null,"expected":"95437","mismatch":false,"report":{"total":48381165}},"#,
+                r#"{"day":7,"input":"alice","part":2,"status":"failed","answer":null,"error":"no \"/\" dir","expected":"24933642","mismatch":true,"report":null}]"#,
+            )
+        );
+        assert_eq!(render_json(&[]), "[]");
+    }
+
+    #[test]
+    fn test_plan_jobs() {
+        let inputs = BTreeMap::from([
+            (1, vec![PathBuf::from("day01/alice.txt")]),
+            (
+                7,
+                vec![
+                    PathBuf::from("day07/alice.txt"),
+                    PathBuf::from("day07/bob.txt"),
+                ],
+            ),
+        ]);
+
+        let jobs = plan_jobs(&inputs, None, |_| true).unwrap();
+        assert_eq!(jobs.len(), 6);
+
+        // part1 のバイナリしかない
+        let jobs = plan_jobs(&inputs, Some(7), |job| job.part == 1).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs.iter().all(|job| job.day == 7 && job.part == 1));
+
+        let e = plan_jobs(&inputs, None, |_| false).unwrap_err();
+        assert!(e.to_string().starts_with("no day binaries found"));
+        let e = plan_jobs(&inputs, Some(3), |_| true).unwrap_err();
+        assert_eq!(e.to_string(), "no input files found");
+        assert!(plan_jobs(&BTreeMap::new(), None, |_| true).is_err());
+    }
+
+    #[test]
+    fn test_job() {
+        let job = Job {
+            day: 7,
+            part: 2,
+            input: PathBuf::from("inputs/day07/alice.txt"),
+        };
+        assert_eq!(job.bin_name(), "day07_part2");
+        assert_eq!(input_name(&job.input), "alice");
+        assert_eq!(
+            answers_path(&job.input),
+            PathBuf::from("inputs/day07/alice.answers")
+        );
+    }
+}
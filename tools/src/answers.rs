@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+/// 回答ファイルを読み込む。
+/// "part1: 157" の形式で、パート番号ごとの回答を返す。
+/// extract_samples が書き出す `sample.answers` と、runner の `<name>.answers` で共通の形式。
+pub fn read_answers(text: &str) -> HashMap<u32, String> {
+    text.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let part = key.trim().strip_prefix("part")?.parse().ok()?;
+            Some((part, value.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_answers() {
+        let answers = read_answers("part1: CMZ\npart2: MCD\n\n");
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[&1], "CMZ");
+        assert_eq!(answers[&2], "MCD");
+    }
+}
@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail};
+
+use tools::puzzle_page::PuzzlePage;
+
+const USAGE: &str = "usage: extract_samples <page.html> <day directory> [--block N]";
+
+fn main() -> anyhow::Result<()> {
+    let mut paths = Vec::new();
+    let mut block = 0;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--block" => {
+                let n = args.next().ok_or_else(|| anyhow!(USAGE))?;
+                block = n.parse()?;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let [page_path, day_dir] = paths.as_slice() else {
+        bail!(USAGE);
+    };
+
+    let page = PuzzlePage::parse(&fs::read_to_string(page_path)?)?;
+
+    let sample = page
+        .sample(block)
+        .ok_or_else(|| anyhow!("code block {} not found", block))?;
+
+    let data_dir = day_dir.join("data");
+    fs::create_dir_all(&data_dir)?;
+
+    let sample_path = data_dir.join("sample.txt");
+    fs::write(&sample_path, sample)?;
+    println!("wrote: {}", sample_path.display());
+
+    let answers = page.format_answers();
+    if answers.is_empty() {
+        println!("answers not found");
+    } else {
+        let answers_path = data_dir.join("sample.answers");
+        fs::write(&answers_path, &answers)?;
+        println!("wrote: {}", answers_path.display());
+        print!("{}", answers);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use std::env;
+
+/// 入力ファイルのパス。
+/// runner は各日のバイナリに入力ファイルのパスを最初の引数で渡すので、それを使う。
+/// 引数がなければ `./<day>/data/input.txt` (ワークスペースのルートから実行した場合)。
+pub fn input_path(day: &str) -> String {
+    env::args()
+        .nth(1)
+        .unwrap_or_else(|| default_input_path(day))
+}
+
+/// 入力ファイルの後に `--json` が渡されたか。
+/// runner は `--json` のとき、これも渡す。対応するバイナリは `report: <JSON>` の行を出力する。
+pub fn json_requested() -> bool {
+    env::args().skip(2).any(|arg| arg == "--json")
+}
+
+fn default_input_path(day: &str) -> String {
+    format!("./{}/data/input.txt", day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_input_path() {
+        assert_eq!(default_input_path("day07"), "./day07/data/input.txt");
+    }
+}
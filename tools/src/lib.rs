@@ -0,0 +1,3 @@
+pub mod answers;
+pub mod input;
+pub mod puzzle_page;
@@ -0,0 +1,208 @@
+use anyhow::bail;
+
+/// 保存したパズルページの内容
+#[derive(Debug, PartialEq)]
+pub struct PuzzlePage {
+    pub parts: Vec<Part>,
+}
+
+/// パズルのパート(part1, part2)ごとの内容
+#[derive(Debug, PartialEq)]
+pub struct Part {
+    /// `<pre><code>` ブロックの中身
+    pub code_blocks: Vec<String>,
+    /// 例示された回答(強調表示されているもの)
+    pub answer: Option<String>,
+}
+
+impl PuzzlePage {
+    pub fn parse(html: &str) -> anyhow::Result<PuzzlePage> {
+        let parts: Vec<Part> = between(html, "<article class=\"day-desc\">", "</article>")
+            .into_iter()
+            .map(Part::parse)
+            .collect();
+
+        if parts.is_empty() {
+            bail!("puzzle description not found");
+        }
+
+        Ok(PuzzlePage { parts })
+    }
+
+    /// サンプル入力として使うブロック
+    /// パート1のブロックから、指定番目のものを返す。
+    pub fn sample(&self, index: usize) -> Option<&str> {
+        self.parts[0].code_blocks.get(index).map(|s| s.as_str())
+    }
+
+    /// sample_answers.txt 用の文字列
+    /// "part1: 157" の形式で、回答が見つかったパートだけ出力する。
+    pub fn format_answers(&self) -> String {
+        self.parts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, part)| {
+                let answer = part.answer.as_ref()?;
+                Some(format!("part{}: {}\n", i + 1, answer))
+            })
+            .collect()
+    }
+}
+
+impl Part {
+    fn parse(article: &str) -> Part {
+        let code_blocks = between(article, "<pre><code>", "</code></pre>")
+            .into_iter()
+            .map(|block| unescape(&strip_tags(block)))
+            .collect();
+
+        // コードブロック内の強調は、回答ではないので除外しておく。
+        let mut text = String::new();
+        let mut rest = article;
+        while let Some(begin) = rest.find("<pre>") {
+            text.push_str(&rest[..begin]);
+            rest = match rest[begin..].find("</pre>") {
+                Some(end) => &rest[begin + end + "</pre>".len()..],
+                None => "",
+            };
+        }
+        text.push_str(rest);
+
+        // 回答は、最後に強調表示された値とみなす。
+        let answer = between(&text, "<em>", "</em>")
+            .into_iter()
+            .rev()
+            .map(|em| unescape(&strip_tags(em)))
+            .find(|em| is_answer_like(em));
+
+        Part {
+            code_blocks,
+            answer,
+        }
+    }
+}
+
+/// open と close に挟まれた部分をすべて返す。
+fn between<'a>(text: &'a str, open: &str, close: &str) -> Vec<&'a str> {
+    let mut list = Vec::new();
+    let mut rest = text;
+
+    while let Some(begin) = rest.find(open) {
+        let inner = &rest[begin + open.len()..];
+        let Some(end) = inner.find(close) else {
+            break;
+        };
+        list.push(&inner[..end]);
+        rest = &inner[end + close.len()..];
+    }
+
+    list
+}
+
+/// タグを取り除く
+fn strip_tags(text: &str) -> String {
+    let mut ret = String::new();
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => ret.push(c),
+            _ => {}
+        }
+    }
+    ret
+}
+
+/// HTMLエンティティを戻す
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 回答らしい値か判定
+/// 空白を含まず、数字を含むか、大文字だけで構成されているもの。
+fn is_answer_like(text: &str) -> bool {
+    if text.is_empty() || text.chars().any(char::is_whitespace) {
+        return false;
+    }
+
+    text.chars().any(|c| c.is_ascii_digit())
+        || (text.len() >= 2 && text.chars().all(|c| c.is_ascii_uppercase()))
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+
+    const PAGE: &str = indoc! {r#"
+        <main>
+        <article class="day-desc"><h2>--- Day 5: Supply Stacks ---</h2>
+        <p>For example:</p>
+        <pre><code>    [D]
+        [N] [C]
+        [Z] [M] [P]
+         1   2   3
+
+        move 1 from 2 to 1
+        </code></pre>
+        <p>Then, one crate is moved from <code>2</code> to <code>1</code>:</p>
+        <pre><code><em>[D]</em>
+        [N] [C]
+        </code></pre>
+        <p>The Elves just need to know <em>which crate will end up on top</em>.
+        In this example, the top crates are <code>C</code> in stack 1, so you should give the Elves the message <code><em>CMZ</em></code>.</p>
+        <p><em>After the rearrangement procedure completes, what crate ends up on top of each stack?</em></p>
+        </article>
+        <p>Your puzzle answer was <code>ABCDEFGHI</code>.</p>
+        <article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+        <p>In this example, the CrateMover 9001 puts crates in a totally different order: <em><code>MCD</code></em>.</p>
+        </article>
+        </main>
+    "#};
+
+    #[test]
+    fn test_parse() {
+        let page = PuzzlePage::parse(PAGE).unwrap();
+
+        assert_eq!(page.parts.len(), 2);
+        assert_eq!(page.parts[0].code_blocks.len(), 2);
+        assert_eq!(
+            page.sample(0),
+            Some("    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1\n")
+        );
+        // タグは除去されること
+        assert_eq!(page.sample(1), Some("[D]\n[N] [C]\n"));
+        assert_eq!(page.sample(2), None);
+
+        assert_eq!(page.parts[0].answer, Some("CMZ".to_string()));
+        assert_eq!(page.parts[1].answer, Some("MCD".to_string()));
+        assert_eq!(page.format_answers(), "part1: CMZ\npart2: MCD\n");
+
+        // 記事がなければエラー
+        assert!(PuzzlePage::parse("<html></html>").is_err());
+    }
+
+    #[test]
+    fn test_strip_tags_and_unescape() {
+        assert_eq!(strip_tags("<code><em>157</em></code>"), "157");
+        assert_eq!(unescape("a &lt;b&gt; &amp;lt;"), "a <b> &lt;");
+    }
+
+    #[test]
+    fn test_is_answer_like() {
+        assert!(is_answer_like("24000"));
+        assert!(is_answer_like("2=-1=0"));
+        assert!(is_answer_like("CMZ"));
+
+        assert!(!is_answer_like("How many total Calories is that Elf carrying?"));
+        assert!(!is_answer_like("not"));
+        assert!(!is_answer_like("*"));
+    }
+}